@@ -61,7 +61,7 @@ impl Config {
                 println!("Welcome back, {}!\n", username);
             }
         } else {
-            let request_token = block_on_all(egg_mode::request_token(&con_token, "oob")).unwrap();
+            let request_token = block_on_all(egg_mode::request_token_oob(&con_token)).unwrap();
 
             println!("Go to the following URL, sign in, and give me the PIN that comes back:");
             println!("{}", egg_mode::authorize_url(&request_token));
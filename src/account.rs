@@ -0,0 +1,182 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Methods to manage the authenticated user's own profile.
+//!
+//! While the `user` module contains functions to look up and act on other users, this module
+//! contains functions that change the authenticated user's account settings and profile. Using
+//! them requires that your application request write access to authenticated accounts.
+//!
+//! ## Types
+//!
+//! * `ProfileColors`: A set of theme colors to apply to the authenticated user's profile with
+//!   `update_profile_colors`.
+//!
+//! ## Functions
+//!
+//! * `remove_profile_banner`
+//! * `update_profile_colors`
+
+use std::collections::HashMap;
+
+use crate::{auth, error, links, user};
+use crate::common::*;
+
+///Removes the profile banner from the authenticated user's profile.
+///
+///Twitter returns an empty response when this is successful, so the future returned by this
+///function only yields the rate-limit information from the call. Calling this when the user
+///doesn't have a banner set also returns success, so it's safe to call without checking first.
+pub fn remove_profile_banner(token: &auth::Token) -> FutureResponse<()> {
+    let req = auth::post(links::account::REMOVE_PROFILE_BANNER, token, None);
+
+    make_future_with_errors(req, parse_remove_banner)
+}
+
+fn parse_remove_banner(full_resp: String, headers: &Headers) -> Result<Response<()>, error::Error> {
+    //Twitter's errors are handed to us here, so check for those, but otherwise ignore the body
+    if let Ok(mut terrs) = serde_json::from_str::<error::TwitterErrors>(&full_resp) {
+        //code 34 is "Sorry, that page does not exist", which is how Twitter says the user has no
+        //banner to remove
+        if !terrs.contains_code(34) {
+            terrs.meta = response_meta(headers);
+            return Err(error::Error::TwitterError(terrs));
+        }
+    }
+
+    rate_headers(headers)
+}
+
+///Sets the theme colors on the authenticated user's profile.
+///
+///Only the colors that were set in the given `ProfileColors` are changed; the rest are left as
+///they were. On success, the future returned by this function yields the updated user.
+pub fn update_profile_colors(
+    colors: &ProfileColors,
+    token: &auth::Token,
+) -> FutureResponse<user::TwitterUser> {
+    let mut params = HashMap::new();
+
+    let fields = [
+        ("profile_background_color", &colors.background),
+        ("profile_link_color", &colors.link),
+        ("profile_sidebar_border_color", &colors.sidebar_border),
+        ("profile_sidebar_fill_color", &colors.sidebar_fill),
+        ("profile_text_color", &colors.text),
+    ];
+    for &(key, color) in fields.iter() {
+        if let Some(ref color) = *color {
+            add_param(&mut params, key, color.clone());
+        }
+    }
+
+    let req = auth::post(links::account::UPDATE_PROFILE_COLORS, token, Some(&params));
+    make_parsed_future(req)
+}
+
+///A set of theme colors to apply to a profile, for use with `update_profile_colors`.
+///
+///Each color is given as a hex string of three or six digits, like `"0084B4"` or `"fff"`. A
+///leading `#` is accepted and removed. The setters return an `Error::BadParameter` if they're
+///given anything else, so that bad colors are caught before calling Twitter.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileColors {
+    ///The profile's background color.
+    pub background: Option<String>,
+    ///The color of links on the profile.
+    pub link: Option<String>,
+    ///The color of the border of the profile's sidebar.
+    pub sidebar_border: Option<String>,
+    ///The background color of the profile's sidebar.
+    pub sidebar_fill: Option<String>,
+    ///The color of text on the profile.
+    pub text: Option<String>,
+}
+
+impl ProfileColors {
+    ///Creates a new `ProfileColors` that doesn't change any colors.
+    pub fn new() -> ProfileColors {
+        ProfileColors::default()
+    }
+
+    ///Sets the profile's background color.
+    pub fn background(self, color: &str) -> Result<Self, error::Error> {
+        Ok(ProfileColors {
+            background: Some(hex_color(color)?),
+            ..self
+        })
+    }
+
+    ///Sets the color of links on the profile.
+    pub fn link(self, color: &str) -> Result<Self, error::Error> {
+        Ok(ProfileColors {
+            link: Some(hex_color(color)?),
+            ..self
+        })
+    }
+
+    ///Sets the color of the border of the profile's sidebar.
+    pub fn sidebar_border(self, color: &str) -> Result<Self, error::Error> {
+        Ok(ProfileColors {
+            sidebar_border: Some(hex_color(color)?),
+            ..self
+        })
+    }
+
+    ///Sets the background color of the profile's sidebar.
+    pub fn sidebar_fill(self, color: &str) -> Result<Self, error::Error> {
+        Ok(ProfileColors {
+            sidebar_fill: Some(hex_color(color)?),
+            ..self
+        })
+    }
+
+    ///Sets the color of text on the profile.
+    pub fn text(self, color: &str) -> Result<Self, error::Error> {
+        Ok(ProfileColors {
+            text: Some(hex_color(color)?),
+            ..self
+        })
+    }
+}
+
+///Checks that the given string is a three- or six-digit hex color, and strips any leading `#`.
+fn hex_color(color: &str) -> Result<String, error::Error> {
+    let hex = color.trim_start_matches('#');
+
+    if (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(hex.to_string())
+    } else {
+        Err(error::Error::BadParameter(
+            "expected a three- or six-digit hex color",
+            color.to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_remove_banner;
+    use crate::common::Headers;
+    use crate::error;
+
+    #[test]
+    fn parse_missing_banner() {
+        let mut headers = Headers::new();
+        headers.insert("x-rate-limit-remaining", "14".parse().unwrap());
+
+        let resp = parse_remove_banner(String::new(), &headers).unwrap();
+        assert_eq!(resp.rate_limit_remaining, 14);
+
+        let body = r#"{"errors":[{"code":34,"message":"Sorry, that page does not exist."}]}"#;
+        let resp = parse_remove_banner(body.to_string(), &headers).unwrap();
+        assert_eq!(resp.rate_limit_remaining, 14);
+
+        let body = r#"{"errors":[{"code":89,"message":"Invalid or expired token."}]}"#;
+        match parse_remove_banner(body.to_string(), &headers) {
+            Err(error::Error::TwitterError(errs)) => assert!(errs.contains_code(89)),
+            other => panic!("expected a TwitterError, got {:?}", other),
+        }
+    }
+}
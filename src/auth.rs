@@ -10,15 +10,19 @@
 
 use std::borrow::Cow;
 use std::fmt;
+use std::sync::RwLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use base64;
 use futures_core::Future;
-use futures_util::{future, TryFutureExt};
+use futures_util::future::{self, Either};
+use futures_util::{FutureExt, TryFutureExt};
 use hmac::{Hmac, Mac};
 use hyper::{Body, Method, Request};
-use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use lazy_static::lazy_static;
 use rand::{self, Rng};
+use serde::{Deserialize, Serialize};
 use serde_json;
 use sha1::Sha1;
 use url::percent_encoding::{EncodeSet, PercentEncode, utf8_percent_encode};
@@ -123,7 +127,11 @@ fn bearer(token: &str) -> String {
 /// ```rust
 /// let con_token = egg_mode::KeyPair::new("consumer key", "consumer token");
 /// ```
-#[derive(Debug, Clone)]
+///
+/// `KeyPair` implements `Serialize` and `Deserialize`, so it can be saved alongside the rest of an
+/// app's configuration. Note that this writes out the `secret` in plain text, so treat anything it's
+/// serialized into as you would a password.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyPair {
     ///A key used to identify an application or user.
     pub key: Cow<'static, str>,
@@ -312,7 +320,30 @@ impl KeyPair {
 /// // for restrictions, see docs for bearer_token
 /// # }
 /// ```
-#[derive(Debug, Clone)]
+///
+/// For long-running services, [`AutoBearer`] can hold onto the Bearer token for you, loading it
+/// when it's first needed and loading it again if Twitter says it's been invalidated.
+///
+/// [`AutoBearer`]: struct.AutoBearer.html
+///
+/// ## Saving Tokens
+///
+/// `Token` implements `Serialize` and `Deserialize`, so a token obtained through the steps above
+/// can be saved (e.g. to a JSON or TOML config file) and loaded again on the next run, instead of
+/// going through the authentication process every time:
+///
+/// ```rust
+/// # let token = egg_mode::Token::Bearer("bearer token".to_string());
+/// let saved = serde_json::to_string(&token).unwrap();
+/// let loaded: egg_mode::Token = serde_json::from_str(&saved).unwrap();
+/// ```
+///
+/// **WARNING**: Serializing a token writes out *all* of its secrets in plain text. For an access
+/// token, that includes your app's consumer secret as well as the user's access token secret. Keep
+/// the saved file private, out of source control, and out of your logs. If you'd rather not save the
+/// consumer key pair alongside the user's, serialize only the `access` `KeyPair` and rebuild the
+/// `Token` with your consumer key pair when loading it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Token {
     ///An OAuth Access token indicating the request is coming from a specific user.
     Access {
@@ -326,16 +357,14 @@ pub enum Token {
     Bearer(String),
 }
 
-///With the given OAuth header and method parameters, create an OAuth signature and return the
-///header with the signature inline.
-fn sign(
-    header: TwitterOAuth,
-    method: Method,
+///With the given OAuth header and method parameters, assemble the signature base string that gets
+///signed to create an OAuth signature.
+fn signature_base(
+    header: &TwitterOAuth,
+    method: &Method,
     uri: &str,
     params: Option<&ParamList>,
-    con_token: &KeyPair,
-    access_token: Option<&KeyPair>,
-) -> TwitterOAuth {
+) -> String {
     let query_string = {
         let mut sig_params = params.cloned().unwrap_or_default();
 
@@ -374,12 +403,17 @@ fn sign(
         query.join("&")
     };
 
-    let base_str = format!(
+    format!(
         "{}&{}&{}",
         percent_encode(method.as_str()),
         percent_encode(uri),
         percent_encode(&query_string)
-    );
+    )
+}
+
+///Sign the given signature base string with the given tokens, returning the base64-encoded
+///signature.
+fn signature(base_str: &str, con_token: &KeyPair, access_token: Option<&KeyPair>) -> String {
     let key = format!(
         "{}&{}",
         percent_encode(&con_token.secret),
@@ -390,7 +424,21 @@ fn sign(
     let mut digest = Hmac::<Sha1>::new_varkey(key.as_bytes()).expect("Wrong key length");
     digest.input(base_str.as_bytes());
 
-    let signature = Some(base64::encode(&digest.result().code()));
+    base64::encode(&digest.result().code())
+}
+
+///With the given OAuth header and method parameters, create an OAuth signature and return the
+///header with the signature inline.
+fn sign(
+    header: TwitterOAuth,
+    method: Method,
+    uri: &str,
+    params: Option<&ParamList>,
+    con_token: &KeyPair,
+    access_token: Option<&KeyPair>,
+) -> TwitterOAuth {
+    let base_str = signature_base(&header, &method, uri, params);
+    let signature = Some(signature(&base_str, con_token, access_token));
 
     TwitterOAuth {
         signature,
@@ -398,6 +446,63 @@ fn sign(
     }
 }
 
+///The intermediate values used to sign a request, as returned by `sign_request_debug`.
+#[cfg(feature = "test-util")]
+#[derive(Debug, Clone)]
+pub struct SignatureDebug {
+    ///The signature base string, containing the method, URL, and sorted parameters that were
+    ///signed.
+    pub base_string: String,
+    ///The computed signature, base64-encoded.
+    pub signature: String,
+    ///The full `Authorization` header that would be sent with the request.
+    pub header: String,
+}
+
+///Signs a request with a fixed nonce and timestamp, returning the intermediate values that went
+///into the signature.
+///
+///This is meant to help debug authentication failures: since the nonce and timestamp are given
+///instead of generated, the results can be compared against Twitter's documented examples (or
+///another OAuth implementation) to see where a signature differs. `params` should contain every
+///query-string or form parameter that would be sent with the request.
+///
+///This is only available when egg-mode is built with the `test-util` feature.
+#[cfg(feature = "test-util")]
+pub fn sign_request_debug(
+    method: Method,
+    uri: &str,
+    params: &[(&str, &str)],
+    con_token: &KeyPair,
+    access_token: Option<&KeyPair>,
+    nonce: &str,
+    timestamp: u64,
+) -> SignatureDebug {
+    let mut param_list = ParamList::new();
+    for &(k, v) in params {
+        add_param(&mut param_list, k, v);
+    }
+
+    let header = TwitterOAuth {
+        consumer_key: con_token.key.to_string(),
+        nonce: nonce.to_string(),
+        signature: None,
+        timestamp,
+        token: access_token.map(|tok| tok.key.to_string()),
+        callback: None,
+        verifier: None,
+    };
+
+    let base_string = signature_base(&header, &method, uri, Some(&param_list));
+    let header = sign(header, method, uri, Some(&param_list), con_token, access_token);
+
+    SignatureDebug {
+        base_string,
+        signature: header.signature.clone().unwrap_or_default(),
+        header: header.to_string(),
+    }
+}
+
 ///With the given method parameters, return a signed OAuth header.
 fn get_header(
     method: Method,
@@ -436,8 +541,38 @@ fn bearer_request(con_token: &KeyPair) -> String {
     format!("Basic {}", base64::encode(&text))
 }
 
+lazy_static! {
+    static ref USER_AGENT: RwLock<HeaderValue> = RwLock::new(HeaderValue::from_static(concat!(
+        "egg-mode/",
+        env!("CARGO_PKG_VERSION")
+    )));
+}
+
+/// Set the `User-Agent` header that egg-mode sends with every request.
+///
+/// By default, egg-mode identifies itself as `egg-mode/<version>`. Some networks block or flag
+/// requests with an unrecognized user agent, so applications running behind them can use this to
+/// present their own name instead. This applies to every request started after it's called,
+/// across the whole program.
+///
+/// Returns `BadParameter` if the given value can't be sent in an HTTP header, e.g. because it
+/// contains a newline.
+pub fn set_user_agent<S: Into<String>>(user_agent: S) -> Result<(), error::Error> {
+    let user_agent = user_agent.into();
+    let value = HeaderValue::from_str(&user_agent)
+        .map_err(|_| error::Error::BadParameter("a valid User-Agent header value", user_agent))?;
+
+    *USER_AGENT.write().unwrap_or_else(|e| e.into_inner()) = value;
+    Ok(())
+}
+
+/// Returns the `User-Agent` header value set by `set_user_agent`, or the default one.
+pub fn user_agent() -> HeaderValue {
+    USER_AGENT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
 /// Assemble a signed GET request to the given URL with the given parameters.
-pub fn get(uri: &str, token: &Token, params: Option<&ParamList>) -> Request<Body> {
+pub fn get(uri: &str, token: &Token, params: Option<&ParamList>) -> Result<Request<Body>, error::Error> {
     let full_url = if let Some(p) = params {
         let query = p
             .iter()
@@ -472,11 +607,11 @@ pub fn get(uri: &str, token: &Token, params: Option<&ParamList>) -> Request<Body
         }
     }
 
-    request.body(Body::empty()).unwrap()
+    Ok(request.body(Body::empty())?)
 }
 
 /// Assemble a signed POST request to the given URL with the given parameters.
-pub fn post(uri: &str, token: &Token, params: Option<&ParamList>) -> Request<Body> {
+pub fn post(uri: &str, token: &Token, params: Option<&ParamList>) -> Result<Request<Body>, error::Error> {
     let content = "application/x-www-form-urlencoded";
     let body = if let Some(p) = params {
         Body::from(
@@ -514,11 +649,20 @@ pub fn post(uri: &str, token: &Token, params: Option<&ParamList>) -> Request<Bod
         }
     }
 
-    request.body(body).unwrap()
+    Ok(request.body(body)?)
 }
 
 /// Assemble a signed POST request to the given URL with the given JSON body.
-pub fn post_json(uri: &str, token: &Token, body: &serde_json::Value) -> Request<Body> {
+///
+/// This is for endpoints like the DM events API that take their arguments as a JSON body instead
+/// of form parameters. The body is sent with `Content-Type: application/json`. Unlike the form
+/// parameters given to `post`, a JSON body isn't part of the OAuth signature, so the request is
+/// signed as if it had no parameters at all.
+pub fn post_json(
+    uri: &str,
+    token: &Token,
+    body: &serde_json::Value,
+) -> Result<Request<Body>, error::Error> {
     let content = "application/json; charset=UTF-8";
     let body = Body::from(body.to_string());
 
@@ -547,7 +691,7 @@ pub fn post_json(uri: &str, token: &Token, body: &serde_json::Value) -> Request<
         }
     }
 
-    request.body(body).unwrap()
+    Ok(request.body(body)?)
 }
 
 /// With the given consumer KeyPair, ask Twitter for a request KeyPair that can be used to request
@@ -639,7 +783,40 @@ pub fn request_token<S: Into<String>>(con_token: &KeyPair, callback: S) -> Twitt
         ))
     }
 
-    make_future(request.body(Body::empty()).unwrap(), parse_tok)
+    make_future(request.body(Body::empty()).map_err(Into::into), parse_tok)
+}
+
+/// With the given consumer KeyPair, ask Twitter for a request token for PIN-based authorization.
+///
+/// This is the same as calling [`request_token`] with the special callback value `"oob"`. It's
+/// meant for applications like command-line tools that can't receive a redirect from a web
+/// browser: show the user the URL from [`authorize_url`], have them enter the PIN that Twitter
+/// gives them after they sign in, then hand that PIN to [`access_token`] as the verifier.
+///
+/// [`request_token`]: fn.request_token.html
+/// [`authorize_url`]: fn.authorize_url.html
+/// [`access_token`]: fn.access_token.html
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tokio::runtime::current_thread::block_on_all;
+/// # fn main() {
+/// let con_token = egg_mode::KeyPair::new("consumer key", "consumer token");
+/// let req_token = block_on_all(egg_mode::request_token_oob(&con_token)).unwrap();
+///
+/// println!("Go to the following URL, sign in, and enter the PIN:");
+/// println!("{}", egg_mode::authorize_url(&req_token));
+///
+/// let mut pin = String::new();
+/// std::io::stdin().read_line(&mut pin).unwrap();
+///
+/// let (token, user_id, screen_name) =
+///     block_on_all(egg_mode::access_token(con_token, &req_token, pin.trim())).unwrap();
+/// # }
+/// ```
+pub fn request_token_oob(con_token: &KeyPair) -> TwitterFuture<KeyPair> {
+    request_token(con_token, "oob")
 }
 
 /// With the given request KeyPair, return a URL that a user can access to accept or reject an
@@ -786,7 +963,7 @@ pub fn access_token<S: Into<String>>(
     let mut request = Request::post(links::auth::ACCESS_TOKEN);
     request.header(AUTHORIZATION, header.to_string());
 
-    let loader = make_raw_future(request.body(Body::empty()).unwrap());
+    let loader = make_raw_future(request.body(Body::empty()).map_err(Into::into));
     loader.and_then(|resp| future::ok(fetch_urlencoded_auth(&resp, con_token).unwrap()))
 }
 
@@ -867,7 +1044,7 @@ pub fn bearer_token(con_token: &KeyPair) -> TwitterFuture<Token> {
     request.header(CONTENT_TYPE, content);
     let request = request
         .body(Body::from("grant_type=client_credentials"))
-        .unwrap();
+        .map_err(Into::into);
 
     fn parse_tok(full_resp: String, _: &Headers) -> Result<Token, error::Error> {
         let decoded: serde_json::Value = serde_json::from_str(&full_resp)?;
@@ -902,7 +1079,7 @@ pub fn invalidate_bearer(con_token: &KeyPair, token: &Token) -> TwitterFuture<To
     request.header(AUTHORIZATION, auth_header);
     request.header(CONTENT_TYPE, content);
     let body = Body::from(format!("access_token={}", token));
-    let request = request.body(body).unwrap();
+    let request = request.body(body).map_err(Into::into);
 
     fn parse_tok(full_resp: String, _: &Headers) -> Result<Token, error::Error> {
         let decoded: serde_json::Value = serde_json::from_str(&full_resp)?;
@@ -917,6 +1094,122 @@ pub fn invalidate_bearer(con_token: &KeyPair, token: &Token) -> TwitterFuture<To
     make_future(request, parse_tok)
 }
 
+/// A Bearer token that's loaded when it's first needed, and loaded again if it stops working.
+///
+/// Bearer tokens don't expire, but they can be invalidated (with [`invalidate_bearer`], or from
+/// the app's settings on Twitter), after which every call made with them fails. `AutoBearer`
+/// saves the token returned by [`bearer_token`] and hands it out from `token`, so services that
+/// use app-only auth don't need to load and save the token themselves. Wrapping calls in `call`
+/// goes one step further: if a call fails because the saved token is invalid, the token is loaded
+/// again and the call is retried once with the new token.
+///
+/// `AutoBearer` doesn't coordinate concurrent loads of the token: if several calls need a token
+/// before one has been saved, each of them loads its own. To avoid that, wait for `token` once
+/// before using the `AutoBearer` from several calls at once. When several calls fail with the same
+/// invalidated token, only the saved copy of that token is cleared, so calls that fail after a new
+/// token has been saved retry with it instead of loading another one.
+///
+/// [`invalidate_bearer`]: fn.invalidate_bearer.html
+/// [`bearer_token`]: fn.bearer_token.html
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use tokio::runtime::current_thread::block_on_all;
+/// # fn main() {
+/// let con_token = egg_mode::KeyPair::new("consumer key", "consumer secret");
+/// let bearer = egg_mode::AutoBearer::new(con_token);
+///
+/// let user = block_on_all(bearer.call(|token| egg_mode::user::show("rustlang", &token)));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AutoBearer {
+    consumer: KeyPair,
+    cached: RwLock<Option<Token>>,
+}
+
+impl AutoBearer {
+    /// Creates a new `AutoBearer` that loads Bearer tokens with the given consumer KeyPair.
+    ///
+    /// This doesn't load a token yet; the first call to `token` or `call` does that.
+    pub fn new(consumer: KeyPair) -> AutoBearer {
+        AutoBearer {
+            consumer,
+            cached: RwLock::new(None),
+        }
+    }
+
+    /// Returns the saved Bearer token, loading and saving it first if there isn't one.
+    pub fn token(&self) -> impl Future<Output = Result<Token, error::Error>> + '_ {
+        let cached = self.cached.read().unwrap_or_else(|e| e.into_inner()).clone();
+
+        if let Some(token) = cached {
+            Either::Left(future::ready(Ok(token)))
+        } else {
+            Either::Right(bearer_token(&self.consumer).map_ok(move |token| {
+                *self.cached.write().unwrap_or_else(|e| e.into_inner()) = Some(token.clone());
+                token
+            }))
+        }
+    }
+
+    /// Forgets the saved Bearer token, so the next call to `token` loads it again.
+    pub fn clear(&self) {
+        *self.cached.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Forgets the saved Bearer token if it's still the given one, so a token that was saved
+    /// after `stale` was handed out isn't thrown away.
+    fn clear_stale(&self, stale: &Token) {
+        let mut cached = self.cached.write().unwrap_or_else(|e| e.into_inner());
+        let is_stale = match (&*cached, stale) {
+            (Some(Token::Bearer(ref cur)), Token::Bearer(ref stale)) => cur == stale,
+            _ => false,
+        };
+
+        if is_stale {
+            *cached = None;
+        }
+    }
+
+    /// Runs the given call with the saved Bearer token, loading the token again and retrying the
+    /// call once if it fails because the token is invalid.
+    ///
+    /// A call is retried if it returns an HTTP 401 status, or Twitter's error code 89 ("Invalid
+    /// or expired token"). Any other error is returned as-is.
+    pub fn call<'a, F, Fut, T>(
+        &'a self,
+        f: F,
+    ) -> impl Future<Output = Result<T, error::Error>> + 'a
+    where
+        F: Fn(Token) -> Fut + 'a,
+        Fut: Future<Output = Result<T, error::Error>> + 'a,
+        T: 'a,
+    {
+        self.token()
+            .and_then(move |token| {
+                f(token.clone()).map(move |res| Ok::<_, error::Error>((res, token, f)))
+            })
+            .and_then(move |(res, token, f)| match res {
+                Err(ref e) if is_invalid_token(e) => {
+                    self.clear_stale(&token);
+                    Either::Left(self.token().and_then(f))
+                }
+                res => Either::Right(future::ready(res)),
+            })
+    }
+}
+
+/// Returns whether the given error means the token used for the call has been invalidated.
+fn is_invalid_token(err: &error::Error) -> bool {
+    match *err {
+        error::Error::BadStatus { status, .. } => status == hyper::StatusCode::UNAUTHORIZED,
+        error::Error::TwitterError(ref errs) => errs.contains_code(89),
+        _ => false,
+    }
+}
+
 /// If the given tokens are valid, return the user information for the authenticated user.
 ///
 /// If you have cached access tokens, using this method is a convenient way to make sure they're
@@ -931,6 +1224,8 @@ pub fn verify_tokens(token: &Token) -> FutureResponse<crate::user::TwitterUser>
 #[cfg(test)]
 mod tests {
     use super::bearer_request;
+    use crate::common::*;
+    use hyper::Method;
 
     #[test]
     fn bearer_header() {
@@ -942,4 +1237,117 @@ mod tests {
 
         assert_eq!(output, "Basic eHZ6MWV2RlM0d0VFUFRHRUZQSEJvZzpMOHFxOVBaeVJnNmllS0dFS2hab2xHQzB2SldMdzhpRUo4OERSZHlPZw==");
     }
+
+    #[test]
+    fn signature_vector() {
+        //example taken from https://developer.twitter.com/en/docs/basics/authentication/guides/creating-a-signature
+        let con_token = super::KeyPair::new(
+            "xvz1evFS4wEEPTGEFPHBog",
+            "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw",
+        );
+        let access_token = super::KeyPair::new(
+            "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+            "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE",
+        );
+        let uri = "https://api.twitter.com/1.1/statuses/update.json";
+
+        let mut params = ParamList::new();
+        add_param(&mut params, "include_entities", "true");
+        add_param(
+            &mut params,
+            "status",
+            "Hello Ladies + Gentlemen, a signed OAuth request!",
+        );
+
+        let header = super::TwitterOAuth {
+            consumer_key: con_token.key.to_string(),
+            nonce: "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg".to_string(),
+            signature: None,
+            timestamp: 1318622958,
+            token: Some(access_token.key.to_string()),
+            callback: None,
+            verifier: None,
+        };
+
+        let base_str = super::signature_base(&header, &Method::POST, uri, Some(&params));
+        assert_eq!(base_str, "POST&https%3A%2F%2Fapi.twitter.com%2F1.1%2Fstatuses%2Fupdate.json&include_entities%3Dtrue%26oauth_consumer_key%3Dxvz1evFS4wEEPTGEFPHBog%26oauth_nonce%3DkYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg%26oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D1318622958%26oauth_token%3D370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb%26oauth_version%3D1.0%26status%3DHello%2520Ladies%2520%252B%2520Gentlemen%252C%2520a%2520signed%2520OAuth%2520request%2521");
+
+        let signed = super::sign(
+            header,
+            Method::POST,
+            uri,
+            Some(&params),
+            &con_token,
+            Some(&access_token),
+        );
+        assert_eq!(
+            signed.signature.as_ref().map(|s| s.as_str()),
+            Some("hCtSmYh+iHYCEqBWrE7C7hYmtUk=")
+        );
+    }
+
+    #[test]
+    fn clear_stale_bearer() {
+        use super::{AutoBearer, KeyPair, Token};
+
+        let bearer = AutoBearer::new(KeyPair::new("key", "secret"));
+        *bearer.cached.write().unwrap() = Some(Token::Bearer("new".to_string()));
+
+        bearer.clear_stale(&Token::Bearer("old".to_string()));
+        assert!(bearer.cached.read().unwrap().is_some());
+
+        bearer.clear_stale(&Token::Bearer("new".to_string()));
+        assert!(bearer.cached.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_token_errors() {
+        use super::is_invalid_token;
+        use crate::error::{Error, TwitterErrorCode, TwitterErrors};
+
+        let expired = TwitterErrors {
+            errors: vec![TwitterErrorCode {
+                message: "Invalid or expired token.".to_string(),
+                code: 89,
+            }],
+            meta: None,
+        };
+        let limited = TwitterErrors {
+            errors: vec![TwitterErrorCode {
+                message: "Rate limit exceeded".to_string(),
+                code: 88,
+            }],
+            meta: None,
+        };
+
+        assert!(is_invalid_token(&Error::TwitterError(expired)));
+        assert!(is_invalid_token(&Error::BadStatus {
+            status: hyper::StatusCode::UNAUTHORIZED,
+            retry_after: None,
+            meta: None,
+        }));
+        assert!(!is_invalid_token(&Error::TwitterError(limited)));
+        assert!(!is_invalid_token(&Error::BadStatus {
+            status: hyper::StatusCode::FORBIDDEN,
+            retry_after: None,
+            meta: None,
+        }));
+    }
+
+    #[test]
+    fn json_request() {
+        use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+
+        let token = super::Token::Access {
+            consumer: super::KeyPair::new("consumer key", "consumer secret"),
+            access: super::KeyPair::new("access key", "access secret"),
+        };
+        let body = serde_json::json!({ "event": { "type": "message_create" } });
+
+        let req = super::post_json("https://api.twitter.com/1.1/dm.json", &token, &body).unwrap();
+
+        assert_eq!(req.method(), Method::POST);
+        assert_eq!(req.headers()[CONTENT_TYPE], "application/json; charset=UTF-8");
+        assert!(req.headers()[AUTHORIZATION].to_str().unwrap().starts_with("OAuth "));
+    }
 }
@@ -39,6 +39,9 @@
 //! that work in one place, and i can just take a `ListID` from the user and shove it directly into
 //! a `ParamList`.
 //!
+//! `parse_query` goes the other way: it takes the query string of a URL Twitter handed back (like
+//! the `next_results` of a search) and turns it back into a `ParamList`.
+//!
 //! `multiple_names_param` is for when a function takes an `IntoIterator<Item=UserID>` It's
 //! possible to mix and match the use of the `"user_id"` and `"screen_name"` parameters on these
 //! lookup functions, so this saves up all that handling and splits the iterator into two strings:
@@ -58,13 +61,18 @@
 //! destructure the pair right in the signature. `>_>`
 //!
 //! `deserialize_datetime` and `deserialize_mime` are glue functions to read these specific items
-//! out in a `Deserialize` implementation. Twitter always gives timestamps in the same format, so
-//! having that function here saves us from having to write the format out everywhere.
+//! out in a `Deserialize` implementation. The REST API always gives timestamps in the same format,
+//! so having that function here saves us from having to write the format out everywhere. Newer
+//! APIs use RFC 3339 instead, so `parse_datetime` (which `deserialize_datetime` calls) falls back
+//! to that if the usual format doesn't match.
 //!
 //! `merge_by` and its companion type `MergeBy` is a copy of the iterator adapter of the same name
 //! from itertools, because i didn't want to add another dependency onto the great towering pile
 //! that is my dep tree. `>_>`
 //!
+//! `Backoff` is an iterator of retry delays, following Twitter's guidance for how long to wait
+//! before reconnecting a stream. It's public so people can drive their own retry loops with it.
+//!
 //! `max_opt` and `min_opt` are helper functions because i didn't realize that `Option` derived
 //! `PartialOrd` and `Ord` at the time. Strictly speaking they're subtly different because
 //! `std::cmp::{min,max}` require `Ord` and `min_opt` won't reach for the None if it's there,
@@ -73,17 +81,25 @@
 //! ## `Response`
 //!
 //! Also in its own module, `Response` is a public structure that contains rate-limit information
-//! from Twitter, alongside some other desired output. This type is used all over the place in
+//! from Twitter, alongside some other desired output. `RateLimit` is a copy of just that
+//! rate-limit information, for when it's known to be present. This type is used all over the place in
 //! egg-mode, because i wanted to make sure people always had rate-limit information on hand. The
 //! module also contains the types and functions that all web calls go through: the ones that load
 //! a web call, parse out the rate-limit headers, and call some handler to perform final processing
-//! on the result.
+//! on the result. `ResponseMeta` holds a few more headers that are only useful for debugging, so
+//! they're only read when the `response-meta` feature is on.
 //!
 //! `ResponseIterRef`, `ResponseIterMut`, and `ResponseIter` are iterator adaptors on
 //! `Response<Vec<T>>` that copy out the rate-limit information to all the elements of the
 //! contained Vec, individually. There's also a `FromIterator` implementation for
 //! `Response<Vec<T>>`, which takes an iterator of `Response<T>` and loads up the last set of
-//! rate-limit information for the collection as a whole.
+//! rate-limit information for the collection as a whole. `run_bounded` uses that to gather up
+//! the results of several calls that it runs concurrently, with a limit on how many run at once.
+//! `Response::merge_independent` is for when the responses come from different methods, and keeps
+//! each one's rate limit separately in an `IndependentResults`.
+//!
+//! `WithRaw` pairs a parsed response with the original JSON body it came from, for the few places
+//! that let users hold onto the raw text.
 //!
 //! `RawFuture` and `TwitterFuture` are the central `Future` types in egg-mode. `RawFuture` is the
 //! base-line Future that handles all the steps of a web call, loading up the response into a
@@ -97,6 +113,10 @@
 //! Those are contained within the modules that need them - `common::response` only contains
 //! `RawFuture` and `TwitterFuture`.
 //!
+//! `BytesStream` is the streaming counterpart to `RawFuture`: instead of loading the whole
+//! response into a String, it hands out the body in chunks as they arrive. It's made with
+//! `make_bytes_stream`, and is meant for large downloads like media files.
+//!
 //! `make_raw_future` is only exported for `AuthFuture`, otherwise it's called by the other Future
 //! constructors to get the basic load-to-String action.
 //!
@@ -105,7 +125,13 @@
 //!
 //! `make_parsed_future` is the most common `TwitterFuture` constructor, which just uses
 //! `make_response` (which just calls `serde_json` and loads up the rate-limit headers - it's also
-//! exported) as the processor.
+//! exported) as the processor. `make_parsed_future_with_raw` and `make_response_with_raw` are the
+//! same, except they return a `WithRaw` so the body isn't thrown away after parsing. Both treat an
+//! empty body as `null`, so calls that return `204 No Content` can be parsed into `()`.
+//!
+//! `on_rate_limit` sets a global hook that `RawFuture` calls with the rate-limit headers of every
+//! response, as soon as the headers arrive. It's guarded by an `AtomicBool` so it costs next to
+//! nothing when it's not set.
 //!
 //! `rate_headers` is an infra function that takes the `Headers` and returns an empty `Response`
 //! with the rate-limit info parsed out. It's only exported for a couple functions in `list` which
@@ -114,6 +140,7 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::iter::Peekable;
+use std::time::Duration;
 
 use chrono::{self, TimeZone};
 use hyper::header::{HeaderMap, HeaderValue};
@@ -121,7 +148,9 @@ use mime;
 use serde::{Deserialize, Deserializer};
 use serde::de::Error;
 
-use crate::{list, user};
+use url::percent_encoding::percent_decode;
+
+use crate::{error, list, user};
 pub use crate::common::response::*;
 
 mod response;
@@ -131,6 +160,42 @@ pub type Headers = HeaderMap<HeaderValue>;
 ///Convenience type used to hold parameters to an API call.
 pub type ParamList<'a> = HashMap<Cow<'a, str>, Cow<'a, str>>;
 
+///Parses the query string of a URL Twitter gave back, like the `next_results` of a search or the
+///`url` of a place search, into a `ParamList` that can be given back to the same endpoint.
+///
+///The given string can either be a bare query (`key=val&key2=val2`) or start with a `?`. Values
+///are percent-decoded, since they'll be encoded again when the request is signed. Returns
+///`BadUrl` if a parameter is missing its `=` or doesn't decode to valid UTF-8.
+pub fn parse_query<'a>(query: &'a str) -> Result<ParamList<'a>, error::Error> {
+    let query = if query.starts_with('?') { &query[1..] } else { query };
+    let mut params = HashMap::new();
+
+    for item in query.split('&').filter(|item| !item.is_empty()) {
+        let mut kv_iter = item.splitn(2, '=');
+
+        let k = kv_iter.next().ok_or(error::Error::BadUrl)?;
+        let v = kv_iter.next().ok_or(error::Error::BadUrl)?;
+
+        add_param(&mut params, decode_query_part(k)?, decode_query_part(v)?);
+    }
+
+    Ok(params)
+}
+
+fn decode_query_part(part: &str) -> Result<Cow<str>, error::Error> {
+    if part.contains('+') {
+        let part = part.replace('+', " ");
+        let decoded = percent_decode(part.as_bytes())
+            .decode_utf8()
+            .map_err(|_| error::Error::BadUrl)?;
+        Ok(Cow::Owned(decoded.into_owned()))
+    } else {
+        percent_decode(part.as_bytes())
+            .decode_utf8()
+            .map_err(|_| error::Error::BadUrl)
+    }
+}
+
 ///Convenience function to add a key/value parameter to a `ParamList`.
 pub fn add_param<'a, K, V>(list: &mut ParamList<'a>, key: K, value: V) -> Option<Cow<'a, str>>
 where
@@ -269,6 +334,102 @@ where
     }
 }
 
+///How the delays given by a `Backoff` grow.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BackoffKind {
+    ///Each delay is the base delay longer than the one before it.
+    Linear,
+    ///Each delay is twice as long as the one before it.
+    Exponential,
+}
+
+///An iterator of delays to wait between retries of a failed call.
+///
+///Each call to `next` returns how long to wait before the next attempt, growing according to the
+///`BackoffKind` until the delays reach the cap, after which the cap is returned forever. Call
+///`reset` once an attempt succeeds, so the next failure starts over from the base delay.
+///
+///Twitter asks that clients back off linearly after network errors, and exponentially after HTTP
+///errors; `Backoff::network` and `Backoff::http` give the schedules from [Twitter's guide to
+///connecting to streams][guide]. If a stream returns `Error::EnhanceYourCalm`, Twitter asks for
+///an exponential backoff starting at one minute instead. If an `Error::BadStatus` carries a
+///`retry_after` duration, prefer waiting that long over the next delay from the schedule.
+///
+///[guide]: https://developer.twitter.com/en/docs/tweets/filter-realtime/guides/connecting
+///
+///```rust
+///use std::time::Duration;
+///use egg_mode::{Backoff, BackoffKind};
+///
+///let mut backoff = Backoff::new(Duration::from_secs(5), Duration::from_secs(15),
+///                               BackoffKind::Exponential);
+///
+///assert_eq!(backoff.next(), Some(Duration::from_secs(5)));
+///assert_eq!(backoff.next(), Some(Duration::from_secs(10)));
+///assert_eq!(backoff.next(), Some(Duration::from_secs(15)));
+///```
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    cap: Duration,
+    kind: BackoffKind,
+    attempt: u32,
+}
+
+impl Backoff {
+    ///Creates a new `Backoff` that starts at `base` and grows according to `kind`, never waiting
+    ///longer than `cap`.
+    pub fn new(base: Duration, cap: Duration, kind: BackoffKind) -> Backoff {
+        Backoff {
+            base,
+            cap,
+            kind,
+            attempt: 0,
+        }
+    }
+
+    ///Creates a `Backoff` for network errors, which starts at 250 milliseconds and grows linearly
+    ///up to 16 seconds.
+    pub fn network() -> Backoff {
+        Backoff::new(
+            Duration::from_millis(250),
+            Duration::from_secs(16),
+            BackoffKind::Linear,
+        )
+    }
+
+    ///Creates a `Backoff` for HTTP errors, which starts at 5 seconds and doubles up to 320
+    ///seconds.
+    pub fn http() -> Backoff {
+        Backoff::new(
+            Duration::from_secs(5),
+            Duration::from_secs(320),
+            BackoffKind::Exponential,
+        )
+    }
+
+    ///Starts the delays over from the base delay.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+impl Iterator for Backoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let delay = match self.kind {
+            BackoffKind::Linear => self.base.checked_mul(self.attempt.saturating_add(1)),
+            BackoffKind::Exponential => 2u32
+                .checked_pow(self.attempt)
+                .and_then(|factor| self.base.checked_mul(factor)),
+        };
+        self.attempt = self.attempt.saturating_add(1);
+
+        Some(delay.map_or(self.cap, |delay| delay.min(self.cap)))
+    }
+}
+
 pub fn max_opt<T: PartialOrd>(left: Option<T>, right: Option<T>) -> Option<T> {
     match (left, right) {
         (Some(left), Some(right)) => {
@@ -302,10 +463,67 @@ where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(ser)?;
-    let date = (chrono::Utc)
-        .datetime_from_str(&s, "%a %b %d %T %z %Y")
-        .map_err(|e| D::Error::custom(e))?;
-    Ok(date)
+    parse_datetime(&s).map_err(|e| D::Error::custom(e))
+}
+
+///Parses a timestamp from Twitter, either in the format the REST API uses (`Wed Oct 10 20:19:24
+///+0000 2018`) or in RFC 3339 format, which newer APIs use.
+pub fn parse_datetime(s: &str) -> Result<chrono::DateTime<chrono::Utc>, chrono::ParseError> {
+    match (chrono::Utc).datetime_from_str(s, "%a %b %d %T %z %Y") {
+        Ok(date) => Ok(date),
+        Err(e) => chrono::DateTime::parse_from_rfc3339(s)
+            .map(|date| date.with_timezone(&chrono::Utc))
+            .map_err(|_| e),
+    }
+}
+
+pub fn deserialize_id_str<'de, D>(ser: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Num(u64),
+        Str(String),
+    }
+
+    match Id::deserialize(ser)? {
+        Id::Num(id) => Ok(id),
+        Id::Str(s) => s.parse().map_err(|e| D::Error::custom(e)),
+    }
+}
+
+pub fn deserialize_opt_id_str<'de, D>(ser: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Id(#[serde(deserialize_with = "deserialize_id_str")] u64);
+
+    Ok(Option::<Id>::deserialize(ser)?.map(|Id(id)| id))
+}
+
+pub fn deserialize_opt_timestamp_ms<'de, D>(
+    ser: D,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Millis {
+        Num(i64),
+        Str(String),
+    }
+
+    let millis = match Option::<Millis>::deserialize(ser)? {
+        Some(Millis::Num(ms)) => ms,
+        Some(Millis::Str(s)) => s.parse().map_err(|e| D::Error::custom(e))?,
+        None => return Ok(None),
+    };
+
+    Ok(Some(chrono::Utc.timestamp_millis(millis)))
 }
 
 pub fn deserialize_mime<'de, D>(ser: D) -> Result<mime::Mime, D::Error>
@@ -343,4 +561,152 @@ pub(crate) mod tests {
         codepoints_to_bytes(&mut range, unicode);
         assert_eq!(&unicode[range.0..range.1], "Iñtërnâtiônàližætiøn ënd");
     }
+
+    #[test]
+    fn test_parse_query() {
+        let params =
+            parse_query("?max_id=1234&q=%23rustlang%20news&count=4&include_entities=1").unwrap();
+        assert_eq!(params.len(), 4);
+        assert_eq!(params["max_id"], "1234");
+        assert_eq!(params["q"], "#rustlang news");
+
+        let params = parse_query("lat=37.7&long=-122.4&query=san+francisco").unwrap();
+        assert_eq!(params["long"], "-122.4");
+        assert_eq!(params["query"], "san francisco");
+
+        assert!(parse_query("").unwrap().is_empty());
+        assert!(parse_query("?lat").is_err());
+    }
+
+    #[test]
+    fn rate_limit_threshold() {
+        let mut resp = Response {
+            rate_limit: 900,
+            rate_limit_remaining: 80,
+            rate_limit_reset: 1_500_000_000,
+            server_time: None,
+            etag: None,
+            content_length: None,
+            meta: None,
+            response: (),
+        };
+
+        assert!(resp.below_threshold(0.1));
+        assert!(!resp.below_threshold(0.05));
+
+        resp.rate_limit_remaining = -1;
+        assert!(!resp.below_threshold(0.1));
+    }
+
+    #[test]
+    fn parse_timestamps() {
+        let expected = chrono::Utc.ymd(2018, 10, 10).and_hms(20, 19, 24);
+
+        assert_eq!(parse_datetime("Wed Oct 10 20:19:24 +0000 2018").unwrap(), expected);
+        assert_eq!(parse_datetime("2018-10-10T20:19:24Z").unwrap(), expected);
+        assert_eq!(parse_datetime("2018-10-10T22:19:24+02:00").unwrap(), expected);
+        assert!(parse_datetime("not a timestamp").is_err());
+    }
+
+    #[test]
+    fn merge_independent_responses() {
+        let resp = |limit, val| Response {
+            rate_limit: limit,
+            rate_limit_remaining: limit - 1,
+            rate_limit_reset: 1_500_000_000,
+            server_time: None,
+            etag: None,
+            content_length: None,
+            meta: None,
+            response: val,
+        };
+
+        let merged = Response::merge_independent(vec![
+            ("followers", resp(15, 1)),
+            ("friends", resp(-1, 2)),
+            ("blocks", resp(900, 3)),
+        ]);
+
+        assert!(merged.effective_rate_limit().is_none());
+        assert_eq!(merged.results.len(), 3);
+        assert_eq!(merged.results[1], ("friends".to_string(), 2));
+        assert_eq!(merged.rate_limits.len(), 2);
+        assert_eq!(merged.rate_limits["followers"].limit, 15);
+        assert_eq!(merged.rate_limits["blocks"].remaining, 899);
+    }
+
+    #[test]
+    fn parse_empty_body() {
+        let resp: Response<()> = make_response(String::new(), &Headers::new()).unwrap();
+        assert_eq!(resp.rate_limit, -1);
+
+        let resp: Response<Option<u64>> = make_response(" ".to_string(), &Headers::new()).unwrap();
+        assert_eq!(resp.response, None);
+
+        let resp: Result<Response<u64>, _> = make_response(String::new(), &Headers::new());
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn parse_error_body() {
+        let body = r#"{"errors":[{"code":34,"message":"Sorry, that page does not exist."}]}"#;
+        let resp: Result<Response<Vec<u64>>, _> = make_response(body.to_string(), &Headers::new());
+
+        match resp {
+            Err(error::Error::TwitterError(errs)) => assert!(errs.contains_code(34)),
+            other => panic!("expected a TwitterError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_retry_after() {
+        let mut headers = Headers::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(hyper::header::RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            hyper::header::RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(0)));
+
+        let later = chrono::Utc::now() + chrono::Duration::seconds(600);
+        let later = later.to_rfc2822();
+        headers.insert(hyper::header::RETRY_AFTER, HeaderValue::from_str(&later).unwrap());
+        let wait = retry_after(&headers).unwrap();
+        assert!(wait > Duration::from_secs(590) && wait <= Duration::from_secs(600));
+
+        headers.insert(hyper::header::RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let backoff = Backoff::http();
+        let delays: Vec<u64> = backoff.take(9).map(|d| d.as_secs()).collect();
+
+        assert_eq!(delays, vec![5, 10, 20, 40, 80, 160, 320, 320, 320]);
+
+        let mut backoff = Backoff::new(
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+            BackoffKind::Exponential,
+        );
+        assert_eq!(backoff.nth(100), Some(Duration::from_secs(60)));
+        backoff.reset();
+        assert_eq!(backoff.next(), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn linear_backoff() {
+        let backoff = Backoff::network();
+        let delays: Vec<u128> = backoff.take(66).map(|d| d.as_millis()).collect();
+
+        assert_eq!(&delays[..4], &[250, 500, 750, 1000]);
+        assert_eq!(delays[63], 16000);
+        assert_eq!(delays[64], 16000);
+        assert_eq!(delays[65], 16000);
+    }
 }
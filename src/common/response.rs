@@ -5,25 +5,33 @@
 //! Infrastructure types related to packaging rate-limit information alongside responses from
 //! Twitter.
 
-use std::{io, mem, slice, vec};
+use std::{mem, slice, vec};
+use std::collections::HashMap;
 use std::iter::FromIterator;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-use futures_core::{Future, Poll};
+use chrono;
+use futures_core::{Future, Poll, Stream};
 use futures_core::task::Context;
-use futures_util::{FutureExt, TryStreamExt};
-use hyper::{self, Body, Request, StatusCode};
+use futures_util::{stream, FutureExt, StreamExt, TryFutureExt, TryStreamExt};
+use hyper::{self, Body, Chunk, Request, StatusCode};
 use hyper::client::ResponseFuture;
-use hyper::header::CONTENT_LENGTH;
+use hyper::header::{CONTENT_LENGTH, DATE, ETAG, RETRY_AFTER, USER_AGENT};
 #[cfg(feature = "native_tls")]
 use hyper_tls::HttpsConnector;
+use lazy_static::lazy_static;
 use serde::Deserialize;
+use serde::de::IgnoredAny;
 use serde_json;
 
 #[cfg(feature = "hyper-rustls")]
 use hyper_rustls::HttpsConnector;
 
+use crate::auth;
 use crate::error::{self, TwitterErrors};
 use crate::error::Error::*;
 
@@ -56,6 +64,75 @@ fn rate_limit_reset(headers: &Headers) -> Result<Option<i32>, error::Error> {
     rate_limit(headers, X_RATE_LIMIT_RESET)
 }
 
+///Reads the `Date` header as a Unix timestamp. Since this is only used to correct for clock skew,
+///a missing or malformed header is ignored instead of returning an error.
+fn server_time(headers: &Headers) -> Option<i32> {
+    let date = headers.get(DATE)?.to_str().ok()?;
+    let date = chrono::DateTime::parse_from_rfc2822(date).ok()?;
+    Some(date.timestamp() as i32)
+}
+
+///Reads the `Retry-After` header as the amount of time to wait before trying the request again.
+///
+///The header can either be a number of seconds or an HTTP date. A date in the past is read as a
+///zero-length wait, and a missing or malformed header is ignored.
+pub fn retry_after(headers: &Headers) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or_else(|_| Duration::from_secs(0)))
+}
+
+///Reads the `Content-Length` header. Like `server_time`, this is only informational, so a missing
+///or malformed header is ignored.
+fn content_length(headers: &Headers) -> Option<usize> {
+    headers.get(CONTENT_LENGTH)?.to_str().ok()?.parse().ok()
+}
+
+///Reads the diagnostic headers saved in `ResponseMeta`, if the `response-meta` feature is enabled.
+#[cfg(feature = "response-meta")]
+pub fn response_meta(headers: &Headers) -> Option<ResponseMeta> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+
+    Some(ResponseMeta {
+        response_time: header("x-response-time").and_then(|t| t.parse().ok()),
+        connection_hash: header("x-connection-hash"),
+        transaction: header("x-transaction").or_else(|| header("x-transaction-id")),
+    })
+}
+
+#[cfg(not(feature = "response-meta"))]
+pub fn response_meta(_: &Headers) -> Option<ResponseMeta> {
+    None
+}
+
+///Diagnostic information Twitter sends in the headers of each response.
+///
+///This is only collected when egg-mode's `response-meta` feature is enabled; otherwise, the `meta`
+///field of every `Response` is `None`. When reporting a problem with the API to Twitter, they may
+///ask for the `transaction` ID of the failing call; for calls that return an error, it's available
+///from `Error::meta`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseMeta {
+    ///How long Twitter took to handle the request, in milliseconds, from the `x-response-time`
+    ///header.
+    pub response_time: Option<u64>,
+    ///The `x-connection-hash` header, which identifies the connection the response came from.
+    pub connection_hash: Option<String>,
+    ///The ID Twitter gave the request, from the `x-transaction` header.
+    pub transaction: Option<String>,
+}
+
 ///A helper struct to wrap response data with accompanying rate limit information.
 ///
 ///This is returned by any function that calls a rate-limited method on Twitter, to allow for
@@ -75,6 +152,32 @@ pub struct Response<T> {
     ///The UTC Unix timestamp at which the rate window resets.
     #[serde(rename = "reset")]
     pub rate_limit_reset: i32,
+    ///The UTC Unix timestamp of when Twitter sent the response, according to Twitter's clock.
+    ///
+    ///This is taken from the `Date` header of the response, if it was present. See
+    ///`reset_relative` for how this is used.
+    #[serde(skip)]
+    pub server_time: Option<i32>,
+    ///The `ETag` header of the response, if Twitter sent one.
+    ///
+    ///This can be saved alongside a cached response and compared with the `etag` of a later
+    ///response, to tell whether the resource changed without comparing the whole body. Responses
+    ///yielded by iterating over a `Response<Vec<T>>` don't carry this, since it describes the whole
+    ///collection.
+    #[serde(skip)]
+    pub etag: Option<String>,
+    ///The `Content-Length` header of the response, if Twitter sent one.
+    ///
+    ///This is the size of the response body in bytes, as sent over the network. Like `etag`, this
+    ///isn't carried by responses yielded by iterating over a `Response<Vec<T>>`.
+    #[serde(skip)]
+    pub content_length: Option<usize>,
+    ///Diagnostic headers Twitter sent with the response, if egg-mode's `response-meta` feature is
+    ///enabled.
+    ///
+    ///Like `etag`, this isn't carried by responses yielded by iterating over a `Response<Vec<T>>`.
+    #[serde(skip)]
+    pub meta: Option<ResponseMeta>,
     ///The decoded response from the request.
     #[serde(default)]
     pub response: T,
@@ -94,9 +197,155 @@ impl<T> Response<T> {
             rate_limit: src.rate_limit,
             rate_limit_remaining: src.rate_limit_remaining,
             rate_limit_reset: src.rate_limit_reset,
+            server_time: src.server_time,
+            etag: src.etag,
+            content_length: src.content_length,
+            meta: src.meta,
             response: fun(src.response),
         }
     }
+
+    ///Consume this `Response` and return the contained response, discarding its rate-limit
+    ///information.
+    ///
+    ///Like `map`, this is not a member function, so as to not conflict with potential methods on
+    ///the contained `T`. Call it as `Response::into_inner(resp)`.
+    pub fn into_inner(src: Response<T>) -> T {
+        src.response
+    }
+
+    ///Consume this `Response` and return the contained response. This is the same as
+    ///`into_inner`.
+    pub fn unwrap_response(src: Response<T>) -> T {
+        Response::into_inner(src)
+    }
+
+    ///Combines the responses from several unrelated calls into one `Response`, tagging each result
+    ///with the name of the call it came from.
+    ///
+    ///Collecting `Response`s into a `Response<Vec<T>>` keeps the tightest rate limit among them,
+    ///which only makes sense when they all came from the same method. Calls to different methods
+    ///have separate rate limits, so this keeps each one in `rate_limits` under the name it was
+    ///given with, and leaves the rate-limit fields of the returned `Response` itself empty. If
+    ///the same name is given more than once, the rate limit of the last response with that name is
+    ///kept.
+    ///
+    ///Like `map`, this is not a member function. Call it as `Response::merge_independent(resps)`.
+    pub fn merge_independent<I, S>(responses: I) -> Response<IndependentResults<T>>
+    where
+        I: IntoIterator<Item = (S, Response<T>)>,
+        S: Into<String>,
+    {
+        let mut merged = IndependentResults {
+            results: Vec::new(),
+            rate_limits: HashMap::new(),
+        };
+
+        for (source, resp) in responses {
+            let source = source.into();
+            if let Some(rate_limit) = resp.effective_rate_limit() {
+                merged.rate_limits.insert(source.clone(), rate_limit);
+            }
+            merged.results.push((source, resp.response));
+        }
+
+        Response {
+            rate_limit: -1,
+            rate_limit_remaining: -1,
+            rate_limit_reset: -1,
+            server_time: None,
+            etag: None,
+            content_length: None,
+            meta: None,
+            response: merged,
+        }
+    }
+
+    ///Returns how long it will be until the rate-limit window resets, or `None` if the response
+    ///didn't include rate-limit information.
+    ///
+    ///`rate_limit_reset` is a timestamp from Twitter's clock, so comparing it against the local
+    ///clock can give the wrong result if the local clock is off. If the response included its
+    ///`Date` header (saved in `server_time`), this measures from that time instead, so the result
+    ///is relative to when the response was sent, regardless of the local clock. Otherwise, this
+    ///falls back to measuring from the local clock. If the reset time has already passed, this
+    ///returns a zero `Duration`.
+    pub fn reset_relative(&self) -> Option<Duration> {
+        if self.rate_limit_reset < 0 {
+            return None;
+        }
+
+        let now = self
+            .server_time
+            .map(i64::from)
+            .unwrap_or_else(|| chrono::Utc::now().timestamp());
+        let remaining = i64::from(self.rate_limit_reset) - now;
+
+        Some(Duration::from_secs(remaining.max(0) as u64))
+    }
+
+    ///Returns whether the remaining calls in this rate-limit window have dropped below the given
+    ///fraction of the limit, i.e. whether `rate_limit_remaining < rate_limit * fraction`.
+    ///
+    ///This lets an app slow down before it runs out of calls entirely, e.g. by spacing out its
+    ///remaining calls over the time returned by `reset_relative`. If the response didn't include
+    ///rate-limit information, this returns `false`.
+    pub fn below_threshold(&self, fraction: f64) -> bool {
+        self.effective_rate_limit()
+            .map_or(false, |rl| f64::from(rl.remaining) < f64::from(rl.limit) * fraction)
+    }
+
+    ///Returns the rate-limit information in this `Response`, or `None` if it's not available.
+    ///
+    ///Most calls fill in the rate-limit fields from the response headers, but some (like the
+    ///per-method entries in `service::rate_limit_status`) load them from the response body
+    ///instead. Either way, any field that wasn't given is set to `-1`. This checks for that, so
+    ///code that handles responses from any call can read the rate limit the same way.
+    pub fn effective_rate_limit(&self) -> Option<RateLimit> {
+        if self.rate_limit < 0 || self.rate_limit_remaining < 0 || self.rate_limit_reset < 0 {
+            return None;
+        }
+
+        Some(RateLimit {
+            limit: self.rate_limit,
+            remaining: self.rate_limit_remaining,
+            reset: self.rate_limit_reset,
+        })
+    }
+}
+
+///Rate-limit information taken from a `Response`, as returned by `Response::effective_rate_limit`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    ///The rate limit ceiling for the given request.
+    pub limit: i32,
+    ///The number of requests left for the 15-minute window.
+    pub remaining: i32,
+    ///The UTC Unix timestamp at which the rate window resets.
+    pub reset: i32,
+}
+
+///The results of several unrelated calls, as returned by `Response::merge_independent`.
+#[derive(Debug, Clone)]
+pub struct IndependentResults<T> {
+    ///The result of each call, alongside the name it was given with, in the order they were
+    ///given.
+    pub results: Vec<(String, T)>,
+    ///The rate-limit information of each call, by name. Calls that didn't return rate-limit
+    ///information aren't included.
+    pub rate_limits: HashMap<String, RateLimit>,
+}
+
+///A parsed response, alongside the original JSON body it was parsed from.
+///
+///This is returned by methods like `direct::Timeline::call_raw`, for when the exact text that
+///Twitter returned needs to be saved, e.g. to archive it or to parse it again later.
+#[derive(Debug, Clone)]
+pub struct WithRaw<T> {
+    ///The parsed response.
+    pub parsed: T,
+    ///The response body exactly as Twitter returned it.
+    pub raw: String,
 }
 
 impl<T> Response<Vec<T>> {
@@ -107,6 +356,7 @@ impl<T> Response<Vec<T>> {
             rate_limit: self.rate_limit,
             rate_limit_remaining: self.rate_limit_remaining,
             rate_limit_reset: self.rate_limit_reset,
+            server_time: self.server_time,
             resp_iter: self.response.iter(),
         }
     }
@@ -118,6 +368,7 @@ impl<T> Response<Vec<T>> {
             rate_limit: self.rate_limit,
             rate_limit_remaining: self.rate_limit_remaining,
             rate_limit_reset: self.rate_limit_reset,
+            server_time: self.server_time,
             resp_iter: self.response.iter_mut(),
         }
     }
@@ -148,6 +399,7 @@ where
     rate_limit: i32,
     rate_limit_remaining: i32,
     rate_limit_reset: i32,
+    server_time: Option<i32>,
     resp_iter: slice::Iter<'a, T>,
 }
 
@@ -163,6 +415,10 @@ where
                 rate_limit: self.rate_limit,
                 rate_limit_remaining: self.rate_limit_remaining,
                 rate_limit_reset: self.rate_limit_reset,
+                server_time: self.server_time,
+                etag: None,
+                content_length: None,
+                meta: None,
                 response: resp,
             })
         } else {
@@ -185,6 +441,10 @@ where
                 rate_limit: self.rate_limit,
                 rate_limit_remaining: self.rate_limit_remaining,
                 rate_limit_reset: self.rate_limit_reset,
+                server_time: self.server_time,
+                etag: None,
+                content_length: None,
+                meta: None,
                 response: resp,
             })
         } else {
@@ -226,6 +486,7 @@ where
     rate_limit: i32,
     rate_limit_remaining: i32,
     rate_limit_reset: i32,
+    server_time: Option<i32>,
     resp_iter: slice::IterMut<'a, T>,
 }
 
@@ -241,6 +502,10 @@ where
                 rate_limit: self.rate_limit,
                 rate_limit_remaining: self.rate_limit_remaining,
                 rate_limit_reset: self.rate_limit_reset,
+                server_time: self.server_time,
+                etag: None,
+                content_length: None,
+                meta: None,
                 response: resp,
             })
         } else {
@@ -263,6 +528,10 @@ where
                 rate_limit: self.rate_limit,
                 rate_limit_remaining: self.rate_limit_remaining,
                 rate_limit_reset: self.rate_limit_reset,
+                server_time: self.server_time,
+                etag: None,
+                content_length: None,
+                meta: None,
                 response: resp,
             })
         } else {
@@ -303,6 +572,7 @@ pub struct ResponseIter<T> {
     rate_limit: i32,
     rate_limit_remaining: i32,
     rate_limit_reset: i32,
+    server_time: Option<i32>,
     resp_iter: vec::IntoIter<T>,
 }
 
@@ -315,6 +585,10 @@ impl<T> Iterator for ResponseIter<T> {
                 rate_limit: self.rate_limit,
                 rate_limit_remaining: self.rate_limit_remaining,
                 rate_limit_reset: self.rate_limit_reset,
+                server_time: self.server_time,
+                etag: None,
+                content_length: None,
+                meta: None,
                 response: resp,
             })
         } else {
@@ -334,6 +608,10 @@ impl<T> DoubleEndedIterator for ResponseIter<T> {
                 rate_limit: self.rate_limit,
                 rate_limit_remaining: self.rate_limit_remaining,
                 rate_limit_reset: self.rate_limit_reset,
+                server_time: self.server_time,
+                etag: None,
+                content_length: None,
+                meta: None,
                 response: resp,
             })
         } else {
@@ -359,6 +637,7 @@ impl<T> IntoIterator for Response<Vec<T>> {
             rate_limit: self.rate_limit,
             rate_limit_remaining: self.rate_limit_remaining,
             rate_limit_reset: self.rate_limit_reset,
+            server_time: self.server_time,
             resp_iter: self.response.into_iter(),
         }
     }
@@ -375,6 +654,10 @@ impl<T> FromIterator<Response<T>> for Response<Vec<T>> {
             rate_limit: -1,
             rate_limit_remaining: -1,
             rate_limit_reset: -1,
+            server_time: None,
+            etag: None,
+            content_length: None,
+            meta: None,
             response: Vec::new(),
         };
 
@@ -383,12 +666,14 @@ impl<T> FromIterator<Response<T>> for Response<Vec<T>> {
                 resp.rate_limit = item.rate_limit;
                 resp.rate_limit_remaining = item.rate_limit_remaining;
                 resp.rate_limit_reset = item.rate_limit_reset;
+                resp.server_time = item.server_time;
             } else if (item.rate_limit_reset == resp.rate_limit_reset)
                 && (item.rate_limit_remaining < resp.rate_limit_remaining)
             {
                 resp.rate_limit = item.rate_limit;
                 resp.rate_limit_remaining = item.rate_limit_remaining;
                 resp.rate_limit_reset = item.rate_limit_reset;
+                resp.server_time = item.server_time;
             }
             resp.response.push(item.response);
         }
@@ -397,7 +682,87 @@ impl<T> FromIterator<Response<T>> for Response<Vec<T>> {
     }
 }
 
-pub fn get_response(request: Request<Body>) -> Result<ResponseFuture, error::Error> {
+///Runs the given futures, with at most `concurrency` of them in flight at once, and collects their
+///results into one `Response`.
+///
+///This is meant for bulk lookups that have been split into several calls, e.g. loading a large
+///list of users in chunks of 100. Running the calls concurrently is faster than running them one
+///after another, but running all of them at once can use up a rate-limit window very quickly;
+///`concurrency` lets you choose the balance between the two. A `concurrency` of zero is treated
+///as one.
+///
+///The results are collected with the `FromIterator` impl on `Response<Vec<T>>`, so the returned
+///rate-limit information is the most recent one out of all the calls. Note that the results are
+///collected in the order the calls *finish*, not the order they were given in. If any call
+///returns an error, that error is returned and the remaining calls are dropped.
+pub fn run_bounded<T, F>(
+    futures: Vec<F>,
+    concurrency: usize,
+) -> impl Future<Output = Result<Response<Vec<T>>, error::Error>>
+where
+    F: Future<Output = Result<Response<T>, error::Error>>,
+{
+    stream::iter(futures)
+        .buffer_unordered(concurrency.max(1))
+        .try_collect::<Vec<_>>()
+        .map_ok(|resps| resps.into_iter().collect())
+}
+
+type RateLimitHook = Arc<dyn Fn(&str, RateLimit) + Send + Sync>;
+
+lazy_static! {
+    static ref RATE_LIMIT_HOOK: RwLock<Option<RateLimitHook>> = RwLock::new(None);
+}
+
+//checked before touching the lock, so responses don't pay for the hook when it's not set
+static RATE_LIMIT_HOOK_SET: AtomicBool = AtomicBool::new(false);
+
+///Sets a function to be called with the rate-limit information of every response from Twitter.
+///
+///The hook is given the path of the endpoint that was called (e.g. `/1.1/statuses/show.json`) and
+///the rate-limit information from the response headers. It's only called for responses that
+///carry rate-limit headers, and it's called before the response is parsed, so it sees calls that
+///end up returning an error too. This is meant for observability, e.g. to export the remaining
+///budget for each endpoint as a metric, without having to wrap every call.
+///
+///Only one hook can be set at a time; setting a new one replaces the old one. The hook is called
+///from whichever task is polling the response, so it should return quickly.
+pub fn on_rate_limit<F>(hook: F)
+where
+    F: Fn(&str, RateLimit) + Send + Sync + 'static,
+{
+    *RATE_LIMIT_HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(hook));
+    RATE_LIMIT_HOOK_SET.store(true, Ordering::SeqCst);
+}
+
+///Removes the hook set by `on_rate_limit`, if any.
+pub fn clear_rate_limit_hook() {
+    RATE_LIMIT_HOOK_SET.store(false, Ordering::SeqCst);
+    *RATE_LIMIT_HOOK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+///Calls the hook set by `on_rate_limit`, if the given headers contain rate-limit information.
+fn report_rate_limit(path: &str, headers: &Headers) {
+    let limit = match rate_headers(headers).ok().and_then(|r| r.effective_rate_limit()) {
+        Some(limit) => limit,
+        None => return,
+    };
+    let hook = RATE_LIMIT_HOOK.read().unwrap_or_else(|e| e.into_inner()).clone();
+
+    if let Some(hook) = hook {
+        hook(path, limit);
+    }
+}
+
+///The nonstandard HTTP status Twitter returns when an app connects to the Streaming API too
+///often.
+pub const ENHANCE_YOUR_CALM: u16 = 420;
+
+pub fn get_response(mut request: Request<Body>) -> Result<ResponseFuture, error::Error> {
+    if !request.headers().contains_key(USER_AGENT) {
+        request.headers_mut().insert(USER_AGENT, auth::user_agent());
+    }
+
     // TODO: num-cpus?
     #[cfg(feature = "native_tls")]
     let connector = HttpsConnector::new(1)?;
@@ -413,12 +778,16 @@ pub fn get_response(request: Request<Body>) -> Result<ResponseFuture, error::Err
 /// before returning the String.
 #[must_use = "futures do nothing unless polled"]
 pub struct RawFuture {
-    request: Option<Request<Body>>,
+    request: Option<Result<Request<Body>, error::Error>>,
+    path: Option<String>,
     response: Option<ResponseFuture>,
     resp_headers: Option<Headers>,
     resp_status: Option<StatusCode>,
     body_stream: Option<Body>,
     body: Vec<u8>,
+    ///Whether to hand back a body containing Twitter's errors instead of returning them as
+    ///`TwitterError`. Rate-limit and partial errors are still returned as errors.
+    pass_errors: bool,
 }
 
 impl RawFuture {
@@ -432,8 +801,13 @@ impl Future for RawFuture {
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if let Some(req) = self.request.take() {
-            // Todo handle error
-            self.response = Some(get_response(req).unwrap());
+            if RATE_LIMIT_HOOK_SET.load(Ordering::Relaxed) {
+                self.path = req.as_ref().ok().map(|req| req.uri().path().to_string());
+            }
+            match req.and_then(get_response) {
+                Ok(resp) => self.response = Some(resp),
+                Err(e) => return Poll::Ready(Err(e)),
+            }
         }
 
         if let Some(mut resp) = self.response.take() {
@@ -444,6 +818,9 @@ impl Future for RawFuture {
                     return Poll::Pending;
                 }
                 Poll::Ready(Ok(resp)) => {
+                    if let Some(path) = self.path.take() {
+                        report_rate_limit(&path, resp.headers());
+                    }
                     self.resp_headers = Some(resp.headers().clone());
                     self.resp_status = Some(resp.status());
                     if let Some(len) = resp.headers().get(CONTENT_LENGTH) {
@@ -477,19 +854,28 @@ impl Future for RawFuture {
         };
 
         match String::from_utf8(mem::replace(&mut self.body, Vec::new())) {
-            Err(_) => Poll::Ready(Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "stream did not contain valid UTF-8",
-            )
-            .into())),
+            Err(err) => Poll::Ready(Err(err.into())),
             Ok(resp) => {
-                if let Ok(err) = serde_json::from_str::<TwitterErrors>(&resp) {
-                    if err.errors.iter().any(|e| e.code == 88)
+                let meta = response_meta(self.headers());
+
+                if let Ok(mut err) = serde_json::from_str::<TwitterErrors>(&resp) {
+                    err.meta = meta.clone();
+                    if err.contains_code(88)
                         && self.headers().contains_key(X_RATE_LIMIT_RESET)
                     {
                         return Poll::Ready(Err(RateLimit(
                             rate_limit_reset(self.headers())?.unwrap(),
+                            meta,
                         )));
+                    } else if self.resp_status.map_or(false, |st| st.is_success())
+                        && has_data_alongside_errors(&resp)
+                    {
+                        return Poll::Ready(Err(PartialError {
+                            data_body: resp,
+                            errors: err,
+                        }));
+                    } else if self.pass_errors {
+                        return Poll::Ready(Ok(resp));
                     } else {
                         return Poll::Ready(Err(TwitterError(err)));
                     }
@@ -497,22 +883,136 @@ impl Future for RawFuture {
 
                 match self.resp_status.unwrap() {
                     st if st.is_success() => Poll::Ready(Ok(resp)),
-                    st => Poll::Ready(Err(BadStatus(st))),
+                    st if st.as_u16() == ENHANCE_YOUR_CALM => Poll::Ready(Err(EnhanceYourCalm)),
+                    status => Poll::Ready(Err(BadStatus {
+                        status,
+                        retry_after: retry_after(self.headers()),
+                        meta,
+                    })),
                 }
             }
         }
     }
 }
 
+///Returns whether the given JSON object has any top-level keys besides `errors`, i.e. whether
+///Twitter sent some data along with the errors in the response.
+fn has_data_alongside_errors(body: &str) -> bool {
+    serde_json::from_str::<HashMap<String, IgnoredAny>>(body)
+        .map(|obj| obj.keys().any(|k| k != "errors"))
+        .unwrap_or(false)
+}
+
 /// Creates a new `RawFuture` starting with the given `Request`.
-pub fn make_raw_future(request: Request<Body>) -> RawFuture {
+pub fn make_raw_future(request: Result<Request<Body>, error::Error>) -> RawFuture {
     RawFuture {
         request: Some(request),
+        path: None,
         response: None,
         resp_headers: None,
         resp_status: None,
         body_stream: None,
         body: Vec::new(),
+        pass_errors: false,
+    }
+}
+
+/// A `Stream` that yields the body of a web request as it arrives, without loading the whole
+/// response into memory first.
+///
+/// This is useful for large downloads (like media files) where buffering the whole response into a
+/// String, like `RawFuture` does, would be wasteful. Before the body is streamed, the response
+/// status is checked; if it indicates an error, the (small) error body is loaded and inspected for
+/// a `TwitterErrors` just like `RawFuture` does, and that error is returned as the only item in
+/// the stream.
+#[must_use = "streams do nothing unless polled"]
+pub struct BytesStream {
+    request: Option<Result<Request<Body>, error::Error>>,
+    response: Option<ResponseFuture>,
+    body_stream: Option<Body>,
+    error: Option<RawFuture>,
+}
+
+impl Stream for BytesStream {
+    type Item = Result<Chunk, error::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(req) = self.request.take() {
+            match req.and_then(get_response) {
+                Ok(resp) => self.response = Some(resp),
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+        }
+
+        if let Some(mut resp) = self.response.take() {
+            match resp.poll_unpin(cx) {
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Pending => {
+                    self.response = Some(resp);
+                    return Poll::Pending;
+                }
+                Poll::Ready(Ok(resp)) => {
+                    if resp.status().is_success() {
+                        self.body_stream = Some(resp.into_body());
+                    } else {
+                        //hand the error body to a RawFuture so it gets the same inspection as a
+                        //regular call
+                        self.error = Some(RawFuture {
+                            request: None,
+                            path: None,
+                            response: None,
+                            resp_headers: Some(resp.headers().clone()),
+                            resp_status: Some(resp.status()),
+                            body_stream: Some(resp.into_body()),
+                            body: Vec::new(),
+                            pass_errors: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(mut err) = self.error.take() {
+            return match err.poll_unpin(cx) {
+                Poll::Pending => {
+                    self.error = Some(err);
+                    Poll::Pending
+                }
+                Poll::Ready(Err(e)) => Poll::Ready(Some(Err(e))),
+                Poll::Ready(Ok(_)) => Poll::Ready(Some(Err(BadStatus {
+                    status: err.resp_status.unwrap(),
+                    retry_after: retry_after(err.headers()),
+                    meta: response_meta(err.headers()),
+                }))),
+            };
+        }
+
+        if let Some(mut body) = self.body_stream.take() {
+            match body.try_poll_next_unpin(cx) {
+                Poll::Pending => {
+                    self.body_stream = Some(body);
+                    Poll::Pending
+                }
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.body_stream = Some(body);
+                    Poll::Ready(Some(Ok(chunk)))
+                }
+                Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(None) => Poll::Ready(None),
+            }
+        } else {
+            Poll::Ready(None)
+        }
+    }
+}
+
+/// Creates a new `BytesStream` starting with the given `Request`.
+pub fn make_bytes_stream(request: Result<Request<Body>, error::Error>) -> BytesStream {
+    BytesStream {
+        request: Some(request),
+        response: None,
+        body_stream: None,
+        error: None,
     }
 }
 
@@ -560,12 +1060,77 @@ pub fn make_response<T: for<'a> Deserialize<'a>>(
     full_resp: String,
     headers: &Headers,
 ) -> Result<Response<T>, error::Error> {
-    let out = serde_json::from_str(&full_resp)?;
+    let out = parse_body(&full_resp).map_err(|e| with_meta(e, headers))?;
+    Ok(Response::map(rate_headers(headers)?, |_| out))
+}
+
+///Parses the given JSON like `make_response`, but also keeps the original body in the returned
+///`WithRaw`.
+pub fn make_response_with_raw<T: for<'a> Deserialize<'a>>(
+    full_resp: String,
+    headers: &Headers,
+) -> Result<Response<WithRaw<T>>, error::Error> {
+    let parsed = parse_body(&full_resp).map_err(|e| with_meta(e, headers))?;
+    let out = WithRaw {
+        parsed,
+        raw: full_resp,
+    };
     Ok(Response::map(rate_headers(headers)?, |_| out))
 }
 
+///Attaches the diagnostic headers of the given response to the given error, if it's a set of
+///errors from Twitter.
+fn with_meta(err: error::Error, headers: &Headers) -> error::Error {
+    match err {
+        TwitterError(mut errs) => {
+            errs.meta = response_meta(headers);
+            TwitterError(errs)
+        }
+        err => err,
+    }
+}
+
+///Parses a response body like `parse_json`, except that an empty body is treated like `null`.
+///
+///Some calls (like marking DMs as read) return `204 No Content` with no body at all when they're
+///successful, so this lets those be parsed into `()` (or an `Option`) instead of failing. Types
+///that can't be made from `null` still fail on an empty body, with the same error as before.
+fn parse_body<T: for<'a> Deserialize<'a>>(body: &str) -> Result<T, error::Error> {
+    if body.trim().is_empty() {
+        if let Ok(out) = serde_json::from_str("null") {
+            return Ok(out);
+        }
+    }
+
+    parse_json(body)
+}
+
+///Parses the given JSON into the given type.
+///
+///This is the same as `serde_json::from_str`, except that if a value nested inside the JSON can't
+///be parsed, the error records the path to that value as well. If the JSON is a set of errors from
+///Twitter instead, they're returned as a `TwitterError`.
+pub fn parse_json<T: for<'a> Deserialize<'a>>(input: &str) -> Result<T, error::Error> {
+    let mut deser = serde_json::Deserializer::from_str(input);
+    let out = serde_path_to_error::deserialize(&mut deser).map_err(|err| {
+        if let Ok(errs) = serde_json::from_str::<TwitterErrors>(input) {
+            return TwitterError(errs);
+        }
+
+        let path = err.path().to_string();
+        if path == "." {
+            DeserializeError(err.into_inner())
+        } else {
+            DeserializeErrorAt(path, err.into_inner())
+        }
+    })?;
+    deser.end()?;
+
+    Ok(out)
+}
+
 pub fn make_future<T>(
-    request: Request<Body>,
+    request: Result<Request<Body>, error::Error>,
     make_resp: fn(String, &Headers) -> Result<T, error::Error>,
 ) -> TwitterFuture<T> {
     TwitterFuture {
@@ -574,18 +1139,50 @@ pub fn make_future<T>(
     }
 }
 
+/// Creates a `TwitterFuture` like `make_future`, except that if Twitter returns an error, its
+/// body is handed to `make_resp` instead of being returned as an `Error::TwitterError`.
+///
+/// This is for calls where Twitter reports an expected answer as an error, like "this user is not
+/// in this list". `make_resp` can parse the body with `make_response`, which still returns Twitter's
+/// errors as `TwitterError`, and turn the expected code into a regular response with the
+/// rate-limit information from `rate_headers`, since Twitter sends that alongside the error.
+pub fn make_future_with_errors<T>(
+    request: Result<Request<Body>, error::Error>,
+    make_resp: fn(String, &Headers) -> Result<T, error::Error>,
+) -> TwitterFuture<T> {
+    let mut request = make_raw_future(request);
+    request.pass_errors = true;
+
+    TwitterFuture {
+        request,
+        make_resp: make_resp,
+    }
+}
+
 /// Shortcut function to create a `TwitterFuture` that parses out the given type from its response.
 pub fn make_parsed_future<T: for<'de> Deserialize<'de>>(
-    request: Request<Body>,
+    request: Result<Request<Body>, error::Error>,
 ) -> TwitterFuture<Response<T>> {
     make_future(request, make_response)
 }
 
+/// Shortcut function to create a `TwitterFuture` that parses out the given type from its response,
+/// while keeping the original response body around as well.
+pub fn make_parsed_future_with_raw<T: for<'de> Deserialize<'de>>(
+    request: Result<Request<Body>, error::Error>,
+) -> TwitterFuture<Response<WithRaw<T>>> {
+    make_future(request, make_response_with_raw)
+}
+
 pub fn rate_headers(resp: &Headers) -> Result<Response<()>, error::Error> {
     Ok(Response {
         rate_limit: rate_limit_limit(resp)?.unwrap_or(-1),
         rate_limit_remaining: rate_limit_remaining(resp)?.unwrap_or(-1),
         rate_limit_reset: rate_limit_reset(resp)?.unwrap_or(-1),
+        server_time: server_time(resp),
+        etag: resp.get(ETAG).and_then(|v| v.to_str().ok()).map(String::from),
+        content_length: content_length(resp),
+        meta: response_meta(resp),
         response: (),
     })
 }
@@ -13,10 +13,10 @@ use std::pin::Pin;
 
 use futures_core::{Poll, Stream};
 use futures_core::task::Context;
-use futures_util::{FutureExt, StreamExt};
+use futures_util::FutureExt;
 use serde::Deserialize;
 
-use crate::{auth, list, user};
+use crate::{auth, error, list, user};
 use crate::common::*;
 
 ///Trait to generalize over paginated views of API results.
@@ -324,51 +324,106 @@ where
     }
 }
 
-impl<'a, T> Stream for CursorIter<'a, T>
+impl<'a, T> CursorIter<'a, T>
 where
     T: Cursor + for<'de> Deserialize<'de> + 'a,
     <T as Cursor>::Item: std::marker::Unpin,
 {
-    type Item = Response<T::Item>;
+    ///Converts this `CursorIter` into a `Stream` that passes errors through instead of ending the
+    ///stream.
+    ///
+    ///The `Stream` implementation on `CursorIter` itself stops when it encounters an error, which
+    ///makes it impossible to tell a network error apart from the end of the collection. The
+    ///returned [`CursorStream`] yields `Result`s instead, ending only once the last page (the one
+    ///with a `next_cursor` of zero) has been consumed. After an error, polling the stream again
+    ///will retry the page that failed.
+    ///
+    ///[`CursorStream`]: struct.CursorStream.html
+    pub fn into_stream(self) -> CursorStream<'a, T> {
+        CursorStream { inner: self }
+    }
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let mut mut_self = self.get_mut();
-        if let Some(mut fut) = mut_self.loader.take() {
+    fn poll_item(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Response<T::Item>, error::Error>>> {
+        if let Some(mut fut) = self.loader.take() {
             match fut.poll_unpin(cx) {
                 Poll::Pending => {
-                    mut_self.loader = Some(fut);
+                    self.loader = Some(fut);
                     return Poll::Pending;
                 }
                 Poll::Ready(Ok(resp)) => {
-                    mut_self.previous_cursor = resp.previous_cursor_id();
-                    mut_self.next_cursor = resp.next_cursor_id();
+                    self.previous_cursor = resp.previous_cursor_id();
+                    self.next_cursor = resp.next_cursor_id();
 
                     let resp = Response::map(resp, |r| r.into_inner());
 
                     let mut iter = resp.into_iter();
                     let first = iter.next();
-                    mut_self.iter = Some(iter);
+                    self.iter = Some(iter);
 
                     match first {
-                        Some(item) => return Poll::Ready(Some(item)),
+                        Some(item) => return Poll::Ready(Some(Ok(item))),
                         None => return Poll::Ready(None),
                     }
                 }
-                Poll::Ready(Err(_)) => {
-                    return Poll::Ready(None);
+                Poll::Ready(Err(e)) => {
+                    return Poll::Ready(Some(Err(e)));
                 }
             }
         }
 
-        if let Some(ref mut results) = mut_self.iter {
+        if let Some(ref mut results) = self.iter {
             if let Some(item) = results.next() {
-                return Poll::Ready(Some(item));
-            } else if mut_self.next_cursor == 0 {
+                return Poll::Ready(Some(Ok(item)));
+            } else if self.next_cursor == 0 {
                 return Poll::Ready(None);
             }
         }
 
-        mut_self.loader = Some(mut_self.call());
-        mut_self.poll_next_unpin(cx)
+        self.loader = Some(self.call());
+        self.poll_item(cx)
+    }
+}
+
+impl<'a, T> Stream for CursorIter<'a, T>
+where
+    T: Cursor + for<'de> Deserialize<'de> + 'a,
+    <T as Cursor>::Item: std::marker::Unpin,
+{
+    type Item = Response<T::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.get_mut().poll_item(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Some(Ok(item))) => Poll::Ready(Some(item)),
+            Poll::Ready(Some(Err(_))) | Poll::Ready(None) => Poll::Ready(None),
+        }
+    }
+}
+
+///A `Stream` over a cursored collection that yields errors alongside its results.
+///
+///This is returned by [`CursorIter::into_stream`]; see that method for details.
+///
+///[`CursorIter::into_stream`]: struct.CursorIter.html#method.into_stream
+#[must_use = "streams are lazy and do nothing unless polled"]
+pub struct CursorStream<'a, T>
+where
+    T: Cursor + for<'de> Deserialize<'de> + 'a,
+{
+    inner: CursorIter<'a, T>,
+}
+
+impl<'a, T> Stream for CursorStream<'a, T>
+where
+    T: Cursor + for<'de> Deserialize<'de> + 'a,
+    <T as Cursor>::Item: std::marker::Unpin,
+{
+    type Item = Result<Response<T::Item>, error::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().inner.poll_item(cx)
     }
 }
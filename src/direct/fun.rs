@@ -41,7 +41,9 @@ pub fn sent(token: &auth::Token) -> Timeline {
 ///there may be situations where you can't verify the recipient's ability to receive the requested
 ///DM beforehand.
 ///
-///Upon successfully sending the DM, the message will be returned.
+///Upon successfully sending the DM, the message will be returned. This is the same full
+///`DirectMessage` that `received` and `sent` return, with its entities parsed and their ranges
+///converted to byte offsets, so it can be displayed right away without calling `show`.
 pub fn send<'id, T: Into<UserID<'id>>>(
     to: T,
     text: &str,
@@ -75,6 +77,43 @@ pub fn delete(
     make_parsed_future(req)
 }
 
+///Mark the conversation with the given user as read, up to and including the given message.
+///
+///This sets the read receipt that the recipient sees in their conversation with the authenticated
+///user. Twitter returns an empty response when this is successful, so the future returned by this
+///function only yields the rate-limit information from the call.
+pub fn mark_read(
+    last_read_event_id: u64,
+    recipient_id: u64,
+    token: &auth::Token,
+) -> impl Future<Output = Result<Response<()>, error::Error>> {
+    let mut params = HashMap::new();
+    add_param(&mut params, "last_read_event_id", last_read_event_id.to_string());
+    add_param(&mut params, "recipient_id", recipient_id.to_string());
+
+    let req = auth::post(links::direct::MARK_READ, token, Some(&params));
+
+    make_parsed_future(req)
+}
+
+///Show a typing indicator to the given user in their conversation with the authenticated user.
+///
+///The indicator is shown for a few seconds, or until the authenticated user sends a message. To
+///keep it visible for longer, call this again; Twitter recommends waiting a few seconds between
+///calls. Twitter returns an empty response when this is successful, so the future returned by this
+///function only yields the rate-limit information from the call.
+pub fn indicate_typing(
+    recipient_id: u64,
+    token: &auth::Token,
+) -> impl Future<Output = Result<Response<()>, error::Error>> {
+    let mut params = HashMap::new();
+    add_param(&mut params, "recipient_id", recipient_id.to_string());
+
+    let req = auth::post(links::direct::INDICATE_TYPING, token, Some(&params));
+
+    make_parsed_future(req)
+}
+
 ///Create a `ConversationTimeline` loader that can load direct messages as a collection of
 ///pre-sorted conversations.
 ///
@@ -85,3 +124,14 @@ pub fn delete(
 pub fn conversations(token: &auth::Token) -> ConversationTimeline {
     ConversationTimeline::new(token)
 }
+
+///Create a `SingleConversation` loader that can load the direct messages between the
+///authenticated user and the given user.
+///
+///Note that this does not load any messages; you need to call `newest` or `older` for that. See
+///[`SingleConversation`] for details.
+///
+///[`SingleConversation`]: struct.SingleConversation.html
+pub fn conversation_with(user_id: u64, token: &auth::Token) -> SingleConversation {
+    SingleConversation::new(user_id, token)
+}
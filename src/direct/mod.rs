@@ -17,10 +17,20 @@
 //!
 //! * `DirectMessage`/`DMEntities`: A single DM and its associated entities. The `DMEntities`
 //!   struct contains information about URLs, user mentions, and hashtags in the DM.
+//! * `DMReaction`: A reaction left on a DM, as listed in its `reactions`.
 //! * `Timeline`: Effectively the same as `tweet::Timeline`, but gives out `DirectMessage`s
 //!   instead.  Returned by functions that traverse collections of DMs.
+//! * `MappedTimeline`: A wrapper around `Timeline` that converts each message it loads with a
+//!   given function. Returned by `Timeline::map`.
+//! * `BlockingIter`: An `Iterator` that pages through a `Timeline` synchronously. Returned by
+//!   `Timeline::blocking_iter`, when the `blocking` feature is enabled.
 //! * `ConversationTimeline`/`DMConversations`: This struct and alias are part of the
 //!   "conversations" wrapper for loading direct messages into per-recipient threads.
+//! * `ConversationExport`/`ExportedMessage`/`MessageDirection`: A serializable copy of the
+//!   threads in a `ConversationTimeline`, for saving a backup of a user's DMs. Returned by
+//!   `ConversationTimeline::export`.
+//! * `SingleConversation`: A loader for the messages between the authenticated user and one other
+//!   user. Returned by `conversation_with`.
 //!
 //! ## Functions
 //!
@@ -33,6 +43,7 @@
 //! * `received`
 //! * `show`
 //! * `conversations`
+//! * `conversation_with`
 //!
 //! ### Actions
 //!
@@ -41,15 +52,19 @@
 //!
 //! * `send`
 //! * `delete`
+//! * `mark_read`
+//! * `indicate_typing`
 
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
 use std::mem;
 
 use chrono;
 use futures_core::Future;
 use futures_util::FutureExt;
 use hyper::{Body, Request};
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{auth, entities, error, user};
 use crate::common::*;
@@ -64,6 +79,10 @@ mod raw;
 ///As a DM has far less metadata than a regular tweet, the structure consequently contains far
 ///fewer fields. The basic fields are `id`, `text`, `entities`, and `created_at`; everything else
 ///either refers to the sender or receiver in some manner.
+///
+///DMs are ordered by `created_at`, with `id` breaking ties, so a collection of DMs from several
+///sources can be sorted into the order they were sent. Two DMs are equal if they have the same
+///`created_at` and `id`; their other fields aren't compared.
 #[derive(Debug)]
 pub struct DirectMessage {
     ///Numeric ID for this DM.
@@ -86,6 +105,33 @@ pub struct DirectMessage {
     pub recipient_id: u64,
     ///Full information for the user who received the DM.
     pub recipient: Box<user::TwitterUser>,
+    ///UTC timestamp from when the recipient read this DM, if Twitter included it.
+    ///
+    ///This is only present on payloads from Twitter's newer DM event API, and only if the recipient
+    ///has read receipts turned on.
+    pub read_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ///The reactions users have left on this DM. This will be empty if Twitter didn't include any.
+    pub reactions: Vec<DMReaction>,
+}
+
+impl PartialEq for DirectMessage {
+    fn eq(&self, other: &DirectMessage) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DirectMessage {}
+
+impl PartialOrd for DirectMessage {
+    fn partial_cmp(&self, other: &DirectMessage) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DirectMessage {
+    fn cmp(&self, other: &DirectMessage) -> Ordering {
+        (self.created_at, self.id).cmp(&(other.created_at, other.id))
+    }
 }
 
 impl<'de> Deserialize<'de> for DirectMessage {
@@ -124,10 +170,26 @@ impl<'de> Deserialize<'de> for DirectMessage {
             recipient_screen_name: raw.recipient_screen_name,
             recipient_id: raw.recipient_id,
             recipient: raw.recipient,
+            read_timestamp: raw.read_timestamp,
+            reactions: raw.reactions,
         })
     }
 }
 
+///A reaction left on a direct message.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DMReaction {
+    ///The ID of the user who left the reaction.
+    #[serde(deserialize_with = "deserialize_id_str")]
+    pub sender_id: u64,
+    ///The kind of reaction, e.g. `"like"` or `"funny"`, or the emoji that was used.
+    #[serde(alias = "emoji")]
+    pub reaction_key: String,
+    ///UTC timestamp from when the reaction was left, if Twitter included it.
+    #[serde(default, deserialize_with = "deserialize_opt_timestamp_ms")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 ///Container for URL, hashtag, mention, and media information associated with a direct message.
 ///
 ///As far as entities are concerned, a DM can contain nearly everything a tweet can. The only thing
@@ -243,7 +305,7 @@ pub struct Timeline {
     ///The maximum number of messages to return in a single call. Twitter doesn't guarantee
     ///returning exactly this number, as suspended or deleted content is removed after retrieving
     ///the initial collection of messages.
-    pub count: i32,
+    pub count: u32,
     ///The largest/most recent DM ID returned in the last call to `start`, `older`, or `newer`.
     pub max_id: Option<u64>,
     ///The smallest/oldest DM ID returned in the last call to `start`, `older`, or `newer`.
@@ -271,11 +333,13 @@ impl Timeline {
         &'s mut self,
         since_id: Option<u64>,
     ) -> impl Future<Output = Result<Response<Vec<DirectMessage>>, error::Error>> + 's {
-        let req = self.request(since_id, self.min_id.map(|id| id - 1));
+        let req = self.request(since_id, self.min_id.map(|id| id.saturating_sub(1)));
         let loader = make_parsed_future(req);
         loader.map(
             move |resp: Result<Response<Vec<DirectMessage>>, error::Error>| {
-                self.map_ids(&resp.as_ref().unwrap().response);
+                if let Ok(ref resp) = resp {
+                    self.map_ids(&resp.response);
+                }
                 resp
             },
         )
@@ -291,9 +355,40 @@ impl Timeline {
         let loader = make_parsed_future(req);
         loader.map(
             move |resp: Result<Response<Vec<DirectMessage>>, error::Error>| {
-                let resp_unwraped = resp.unwrap();
-                self.map_ids(&resp_unwraped.response);
-                Ok(resp_unwraped)
+                if let Ok(ref resp) = resp {
+                    self.map_ids(&resp.response);
+                }
+                resp
+            },
+        )
+    }
+
+    ///Return the set of DMs newer than any this timeline has loaded so far.
+    ///
+    ///This is meant to be called repeatedly to poll for new messages. Unlike `newer`, this only
+    ///ever moves `max_id` forward: if no new messages have arrived, this returns an empty set and
+    ///leaves the timeline alone, so the next call still only loads messages newer than the last
+    ///ones seen. `min_id` is only set if this timeline hadn't loaded any messages before.
+    ///
+    ///If more than `count` messages have arrived since the last call, only the newest `count`
+    ///messages are returned, so poll often enough (or with a large enough page size) to avoid
+    ///missing messages.
+    pub fn poll_new<'s>(
+        &'s mut self,
+    ) -> impl Future<Output = Result<Response<Vec<DirectMessage>>, error::Error>> + 's {
+        let req = self.request(self.max_id, None);
+        let loader = make_parsed_future(req);
+        loader.map(
+            move |resp: Result<Response<Vec<DirectMessage>>, error::Error>| {
+                if let Ok(ref resp) = resp {
+                    if let Some(first) = resp.response.first() {
+                        self.max_id = Some(first.id);
+                    }
+                    if self.min_id.is_none() {
+                        self.min_id = resp.response.last().map(|dm| dm.id);
+                    }
+                }
+                resp
             },
         )
     }
@@ -313,16 +408,120 @@ impl Timeline {
         make_parsed_future(self.request(since_id, max_id))
     }
 
+    ///Return the set of DMs older than the last set pulled, like `older`, alongside the raw JSON
+    ///Twitter returned for them.
+    pub fn older_raw<'s>(
+        &'s mut self,
+        since_id: Option<u64>,
+    ) -> impl Future<Output = Result<Response<WithRaw<Vec<DirectMessage>>>, error::Error>> + 's {
+        let req = self.request(since_id, self.min_id.map(|id| id.saturating_sub(1)));
+        let loader = make_parsed_future_with_raw(req);
+        loader.map(
+            move |resp: Result<Response<WithRaw<Vec<DirectMessage>>>, error::Error>| {
+                if let Ok(ref resp) = resp {
+                    self.map_ids(&resp.response.parsed);
+                }
+                resp
+            },
+        )
+    }
+
+    ///Return the set of DMs newer than the last set pulled, like `newer`, alongside the raw JSON
+    ///Twitter returned for them.
+    pub fn newer_raw<'s>(
+        &'s mut self,
+        max_id: Option<u64>,
+    ) -> impl Future<Output = Result<Response<WithRaw<Vec<DirectMessage>>>, error::Error>> + 's {
+        let req = self.request(self.max_id, max_id);
+        let loader = make_parsed_future_with_raw(req);
+        loader.map(
+            move |resp: Result<Response<WithRaw<Vec<DirectMessage>>>, error::Error>| {
+                if let Ok(ref resp) = resp {
+                    self.map_ids(&resp.response.parsed);
+                }
+                resp
+            },
+        )
+    }
+
+    ///Return the set of DMs between the IDs given, like `call`, alongside the raw JSON Twitter
+    ///returned for them.
+    ///
+    ///The `raw` field of the returned `WithRaw` is the response body exactly as Twitter sent it,
+    ///so it can be archived or parsed again later.
+    pub fn call_raw(
+        &self,
+        since_id: Option<u64>,
+        max_id: Option<u64>,
+    ) -> impl Future<Output = Result<Response<WithRaw<Vec<DirectMessage>>>, error::Error>> {
+        make_parsed_future_with_raw(self.request(since_id, max_id))
+    }
+
     ///Helper builder function to set the page size.
-    pub fn with_page_size(self, page_size: i32) -> Self {
+    ///
+    ///Twitter accepts page sizes between 1 and 200 when loading direct messages, so the given
+    ///value is clamped to that range.
+    pub fn with_page_size(self, page_size: u32) -> Self {
         Timeline {
-            count: page_size,
+            count: page_size.max(1).min(200),
             ..self
         }
     }
 
+    ///Returns an `Iterator` that loads this timeline's messages synchronously, one page at a time.
+    ///
+    ///The iterator starts where this timeline left off, calling `older` whenever it runs out of
+    ///messages, and blocks the current thread until each page has loaded. It ends when Twitter
+    ///returns an empty page, or after yielding the first error it runs into. To load the newest
+    ///messages, call `reset` before this.
+    ///
+    ///This runs each page on its own Tokio runtime, so it can't be used from inside a running
+    ///runtime; use `older` there instead. It's only available when egg-mode's `blocking` feature
+    ///is enabled.
+    ///
+    ///```rust,no_run
+    ///# use egg_mode::Token;
+    ///# fn main() {
+    ///# let token: Token = unimplemented!();
+    ///let mut timeline = egg_mode::direct::received(&token);
+    ///
+    ///for dm in timeline.blocking_iter() {
+    ///    let dm = dm.unwrap();
+    ///    println!("<@{}> {}", dm.sender_screen_name, dm.text);
+    ///}
+    ///# }
+    ///```
+    #[cfg(feature = "blocking")]
+    pub fn blocking_iter(&mut self) -> BlockingIter {
+        BlockingIter {
+            timeline: self,
+            page: Vec::new().into_iter(),
+            done: false,
+        }
+    }
+
+    ///Wrap this timeline so that it converts every message it loads with the given function.
+    ///
+    ///The returned `MappedTimeline` still tracks the IDs of the messages it loads, so it can be
+    ///paged through like this `Timeline`, but only hands out the converted values. This is useful
+    ///to avoid holding onto whole `DirectMessage`s when only a few fields are needed.
+    pub fn map<U, F>(self, f: F) -> MappedTimeline<U, F>
+    where
+        F: Fn(DirectMessage) -> U,
+    {
+        MappedTimeline {
+            timeline: self,
+            f,
+            _marker: PhantomData,
+        }
+    }
+
     ///Helper function to construct a `Request` from the current state.
-    fn request(&self, since_id: Option<u64>, max_id: Option<u64>) -> Request<Body> {
+    fn request(
+        &self,
+        since_id: Option<u64>,
+        max_id: Option<u64>,
+    ) -> Result<Request<Body>, error::Error> {
         let mut params = self.params_base.as_ref().cloned().unwrap_or_default();
         add_param(&mut params, "count", self.count.to_string());
 
@@ -360,6 +559,123 @@ impl Timeline {
     }
 }
 
+///An `Iterator` that loads the messages of a `Timeline` synchronously, as returned by
+///`Timeline::blocking_iter`.
+///
+///Only available when egg-mode's `blocking` feature is enabled.
+#[cfg(feature = "blocking")]
+pub struct BlockingIter<'a> {
+    timeline: &'a mut Timeline,
+    page: std::vec::IntoIter<DirectMessage>,
+    done: bool,
+}
+
+#[cfg(feature = "blocking")]
+impl<'a> Iterator for BlockingIter<'a> {
+    type Item = Result<DirectMessage, error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(dm) = self.page.next() {
+            return Some(Ok(dm));
+        }
+
+        if self.done {
+            return None;
+        }
+
+        match tokio::runtime::current_thread::block_on_all(self.timeline.older(None)) {
+            Ok(resp) => {
+                if resp.response.is_empty() {
+                    self.done = true;
+                }
+                self.page = resp.response.into_iter();
+                self.page.next().map(Ok)
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+///A `Timeline` that converts the messages it loads before returning them.
+///
+///This is returned by `Timeline::map`, and has the same methods to navigate the timeline as
+///`Timeline` itself. The IDs used to page through the timeline are taken from the messages before
+///they're converted, so the given function doesn't need to keep them around.
+pub struct MappedTimeline<U, F> {
+    ///The timeline being wrapped. Its `min_id` and `max_id` fields track the messages loaded
+    ///through this `MappedTimeline`.
+    pub timeline: Timeline,
+    f: F,
+    _marker: PhantomData<fn() -> U>,
+}
+
+impl<U, F> MappedTimeline<U, F>
+where
+    F: Fn(DirectMessage) -> U,
+{
+    ///Clear the saved IDs on this timeline.
+    pub fn reset(&mut self) {
+        self.timeline.reset();
+    }
+
+    ///Clear the saved IDs on this timeline, and return the most recent set of messages.
+    pub fn start<'s>(
+        &'s mut self,
+    ) -> impl Future<Output = Result<Response<Vec<U>>, error::Error>> + 's {
+        self.reset();
+        self.older(None)
+    }
+
+    ///Return the set of DMs older than the last set pulled, optionally placing a minimum DM ID to
+    ///bound with.
+    pub fn older<'s>(
+        &'s mut self,
+        since_id: Option<u64>,
+    ) -> impl Future<Output = Result<Response<Vec<U>>, error::Error>> + 's {
+        let f = &self.f;
+        self.timeline
+            .older(since_id)
+            .map(move |resp| resp.map(|resp| map_messages(resp, f)))
+    }
+
+    ///Return the set of DMs newer than the last set pulled, optionally placing a maximum DM ID to
+    ///bound with.
+    pub fn newer<'s>(
+        &'s mut self,
+        max_id: Option<u64>,
+    ) -> impl Future<Output = Result<Response<Vec<U>>, error::Error>> + 's {
+        let f = &self.f;
+        self.timeline
+            .newer(max_id)
+            .map(move |resp| resp.map(|resp| map_messages(resp, f)))
+    }
+
+    ///Return the set of DMs between the IDs given.
+    ///
+    ///See `Timeline::call` for the bounds of this range.
+    pub fn call<'s>(
+        &'s self,
+        since_id: Option<u64>,
+        max_id: Option<u64>,
+    ) -> impl Future<Output = Result<Response<Vec<U>>, error::Error>> + 's {
+        let f = &self.f;
+        self.timeline
+            .call(since_id, max_id)
+            .map(move |resp| resp.map(|resp| map_messages(resp, f)))
+    }
+}
+
+///Convert the messages in the given response with the given function.
+fn map_messages<U, F>(resp: Response<Vec<DirectMessage>>, f: &F) -> Response<Vec<U>>
+where
+    F: Fn(DirectMessage) -> U,
+{
+    Response::map(resp, |msgs| msgs.into_iter().map(f).collect())
+}
+
 ///Wrapper around a collection of direct messages, sorted by their recipient.
 ///
 ///The mapping exposed here is from a User ID to a listing of direct messages between the
@@ -409,7 +725,9 @@ fn merge(this: &mut DMConversations, conversations: DMConversations) {
 /// `conversations` field.
 ///
 /// There are two methods to load messages, and they operate by extending the cache by loading
-/// messages either older or newer than the extent of the cache.
+/// messages either older or newer than the extent of the cache. After each call, the `updated`
+/// field contains the IDs of the users whose threads gained messages in that call, so only those
+/// threads need to be redrawn.
 ///
 /// **NOTE**: Twitter has different API limitations for sent versus received messages. You can only
 /// load the most recent 200 *received* messages through the public API, but you can load up to 800
@@ -456,6 +774,9 @@ pub struct ConversationTimeline {
     pub count: u32,
     ///The conversation threads that have been loaded so far.
     pub conversations: DMConversations,
+    ///The user IDs of the conversation threads that gained messages in the last call to `newest`
+    ///or `next`. This is empty if that call didn't load any messages.
+    pub updated: HashSet<u64>,
 }
 
 impl ConversationTimeline {
@@ -469,6 +790,7 @@ impl ConversationTimeline {
             first_received: None,
             count: 20,
             conversations: HashMap::new(),
+            updated: HashSet::new(),
         }
     }
 
@@ -481,10 +803,12 @@ impl ConversationTimeline {
         let sender = sent.first().map(|m| m.sender_id);
         let receiver = received.first().map(|m| m.recipient_id);
 
+        self.updated.clear();
+
         if let Some(me_id) = sender.or(receiver) {
             let mut new_convo = HashMap::new();
 
-            for msg in merge_by(sent, received, |left, right| left.id > right.id) {
+            for msg in merge_by(sent, received, |left, right| left > right) {
                 let recipient = if msg.sender_id == me_id {
                     msg.recipient_id
                 } else {
@@ -495,14 +819,24 @@ impl ConversationTimeline {
                 thread.push(msg);
             }
 
+            self.updated.extend(new_convo.keys().cloned());
             merge(&mut self.conversations, new_convo);
         }
     }
 
     ///Builder function to set the number of messages pulled in a single request.
+    ///
+    ///This is applied to the calls for both sent and received messages. Like
+    ///`Timeline::with_page_size`, the given value is clamped to the range Twitter accepts, between
+    ///1 and 200.
     pub fn with_page_size(self, page_size: u32) -> ConversationTimeline {
+        let sent = self.sent.with_page_size(page_size);
+        let received = self.received.with_page_size(page_size);
+
         ConversationTimeline {
-            count: page_size,
+            count: sent.count,
+            sent,
+            received,
             ..self
         }
     }
@@ -510,7 +844,9 @@ impl ConversationTimeline {
     ///Load messages newer than the currently-loaded set, or the newset set if no messages have
     ///been loaded yet. The complete conversation set can be viewed from the `ConversationTimeline`
     ///after it is finished loading.
-    pub fn newest(self) -> impl Future<Output = ConversationTimeline> {
+    ///
+    ///If loading either the sent or received messages fails, the future returns that error instead.
+    pub fn newest(self) -> impl Future<Output = Result<ConversationTimeline, error::Error>> {
         let sent = self.sent.call(self.last_sent, None);
         let received = self.received.call(self.last_received, None);
 
@@ -520,25 +856,310 @@ impl ConversationTimeline {
     ///Load messages older than the currently-loaded set, or the newest set if no messages have
     ///been loaded. The complete conversation set can be viewed from the `ConversationTimeline`
     ///after it is finished loading.
-    pub fn next(self) -> impl Future<Output = ConversationTimeline> {
+    ///
+    ///If loading either the sent or received messages fails, the future returns that error instead.
+    pub fn next(self) -> impl Future<Output = Result<ConversationTimeline, error::Error>> {
         let sent = self.sent.call(None, self.first_sent);
         let received = self.received.call(None, self.first_received);
 
         self.make_future(sent, received)
     }
 
+    ///Flattens the loaded conversations into a form that can be serialized, e.g. to save a backup
+    ///of the authenticated user's DMs.
+    ///
+    ///`me` is the user ID of the authenticated user, which is used to tell which messages were sent
+    ///and which were received. The conversations are sorted by the ID of the other user, and the
+    ///messages in each one are sorted from oldest to newest.
+    pub fn export(&self, me: u64) -> Vec<ConversationExport> {
+        let mut export = self
+            .conversations
+            .iter()
+            .map(|(&with_user_id, convo)| {
+                let mut messages = convo.iter().collect::<Vec<_>>();
+                messages.sort();
+
+                ConversationExport {
+                    with_user_id,
+                    messages: messages
+                        .into_iter()
+                        .map(|msg| ExportedMessage::new(msg, me))
+                        .collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        export.sort_by_key(|convo| convo.with_user_id);
+        export
+    }
+
     fn make_future<S, R>(
         mut self,
         sent: S,
         received: R,
-    ) -> impl Future<Output = ConversationTimeline>
+    ) -> impl Future<Output = Result<ConversationTimeline, error::Error>>
     where
         S: Future<Output = Result<Response<Vec<DirectMessage>>, error::Error>>,
         R: Future<Output = Result<Response<Vec<DirectMessage>>, error::Error>>,
     {
         futures_util::future::join(sent, received).map(|(sent, recvd)| {
-            self.merge(sent.unwrap().response, recvd.unwrap().response);
-            self
+            self.merge(sent?.response, recvd?.response);
+            Ok(self)
         })
     }
 }
+
+///A single conversation thread, as exported by `ConversationTimeline::export`.
+///
+///This only contains plain data about each message, so its serialized form can be saved and loaded
+///independently of the rest of the library.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationExport {
+    ///The ID of the user on the other side of the conversation.
+    pub with_user_id: u64,
+    ///The messages in the conversation, from oldest to newest.
+    pub messages: Vec<ExportedMessage>,
+}
+
+///A single direct message, as exported by `ConversationTimeline::export`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedMessage {
+    ///Numeric ID for this DM.
+    pub id: u64,
+    ///UTC timestamp from when this DM was created.
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    ///Whether the exporting user sent or received this DM.
+    pub direction: MessageDirection,
+    ///The text of the DM.
+    pub text: String,
+    ///The URLs linked in the DM, resolved to the address they point to instead of their t.co
+    ///links.
+    pub urls: Vec<String>,
+}
+
+impl ExportedMessage {
+    fn new(msg: &DirectMessage, me: u64) -> ExportedMessage {
+        ExportedMessage {
+            id: msg.id,
+            timestamp: msg.created_at,
+            direction: if msg.sender_id == me {
+                MessageDirection::Sent
+            } else {
+                MessageDirection::Received
+            },
+            text: msg.text.clone(),
+            urls: msg.entities.urls.iter().map(|url| url.best_url().to_string()).collect(),
+        }
+    }
+}
+
+///Whether an `ExportedMessage` was sent or received by the user who exported it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageDirection {
+    ///The exporting user sent this message.
+    Sent,
+    ///The exporting user received this message.
+    Received,
+}
+
+/// Helper struct to load the direct messages between the authenticated user and one other user.
+///
+/// Twitter doesn't have an endpoint to load a single conversation, so like `ConversationTimeline`,
+/// this loads pages of both sent and received messages, but only hands out the ones exchanged with
+/// `user_id`. It keeps track of the IDs of every message it loads, so each call picks up where the
+/// last one left off without loading the same page twice.
+///
+/// Since the pages are filtered after they're loaded, a call may return fewer messages than the
+/// page size, or none at all, even if older messages in the conversation exist. To load more of
+/// the conversation, keep calling `older` until it returns an empty set *and* `exhausted` returns
+/// `true`.
+///
+/// The same limits on loading sent and received messages apply here as they do to
+/// [`ConversationTimeline`].
+///
+/// [`ConversationTimeline`]: struct.ConversationTimeline.html
+pub struct SingleConversation {
+    sent: Timeline,
+    received: Timeline,
+    sent_exhausted: bool,
+    received_exhausted: bool,
+    ///The ID of the user on the other side of the conversation.
+    pub user_id: u64,
+    ///The message ID of the most recent sent message loaded so far, from any conversation.
+    pub last_sent: Option<u64>,
+    ///The message ID of the most recent received message loaded so far, from any conversation.
+    pub last_received: Option<u64>,
+    ///The message ID of the oldest sent message loaded so far, from any conversation.
+    pub first_sent: Option<u64>,
+    ///The message ID of the oldest received message loaded so far, from any conversation.
+    pub first_received: Option<u64>,
+}
+
+impl SingleConversation {
+    fn new(user_id: u64, token: &auth::Token) -> SingleConversation {
+        SingleConversation {
+            sent: sent(token),
+            received: received(token),
+            sent_exhausted: false,
+            received_exhausted: false,
+            user_id,
+            last_sent: None,
+            last_received: None,
+            first_sent: None,
+            first_received: None,
+        }
+    }
+
+    ///Builder function to set the number of messages pulled in a single request. Twitter accepts
+    ///page sizes between 1 and 200, and the given value is clamped to that range.
+    pub fn with_page_size(self, page_size: u32) -> SingleConversation {
+        SingleConversation {
+            sent: self.sent.with_page_size(page_size),
+            received: self.received.with_page_size(page_size),
+            ..self
+        }
+    }
+
+    ///Returns whether both the sent and received messages have been loaded back as far as Twitter
+    ///allows, meaning that `older` won't return any more messages.
+    pub fn exhausted(&self) -> bool {
+        self.sent_exhausted && self.received_exhausted
+    }
+
+    ///Load messages in this conversation newer than the ones loaded so far, or the newest set if
+    ///no messages have been loaded yet. The messages are returned newest-first.
+    pub fn newest<'s>(
+        &'s mut self,
+    ) -> impl Future<Output = Result<Vec<DirectMessage>, error::Error>> + 's {
+        let sent = self.sent.call(self.last_sent, None);
+        let received = self.received.call(self.last_received, None);
+
+        self.make_future(sent, received, false)
+    }
+
+    ///Load messages in this conversation older than the ones loaded so far, or the newest set if
+    ///no messages have been loaded yet. The messages are returned newest-first.
+    pub fn older<'s>(
+        &'s mut self,
+    ) -> impl Future<Output = Result<Vec<DirectMessage>, error::Error>> + 's {
+        let sent = self.sent.call(None, self.first_sent.map(|id| id.saturating_sub(1)));
+        let received = self.received.call(None, self.first_received.map(|id| id.saturating_sub(1)));
+
+        self.make_future(sent, received, true)
+    }
+
+    fn make_future<'s, S, R>(
+        &'s mut self,
+        sent: S,
+        received: R,
+        older: bool,
+    ) -> impl Future<Output = Result<Vec<DirectMessage>, error::Error>> + 's
+    where
+        S: Future<Output = Result<Response<Vec<DirectMessage>>, error::Error>> + 's,
+        R: Future<Output = Result<Response<Vec<DirectMessage>>, error::Error>> + 's,
+    {
+        futures_util::future::join(sent, received).map(move |(sent, recvd)| {
+            let sent = sent?.response;
+            let recvd = recvd?.response;
+
+            if older {
+                self.sent_exhausted = sent.is_empty();
+                self.received_exhausted = recvd.is_empty();
+            }
+
+            self.last_sent = max_opt(self.last_sent, sent.first().map(|m| m.id));
+            self.last_received = max_opt(self.last_received, recvd.first().map(|m| m.id));
+            self.first_sent = min_opt(self.first_sent, sent.last().map(|m| m.id));
+            self.first_received = min_opt(self.first_received, recvd.last().map(|m| m.id));
+
+            let user_id = self.user_id;
+            let sent = sent
+                .into_iter()
+                .filter(|m| m.recipient_id == user_id)
+                .collect::<Vec<_>>();
+            let recvd = recvd
+                .into_iter()
+                .filter(|m| m.sender_id == user_id)
+                .collect::<Vec<_>>();
+
+            Ok(merge_by(sent, recvd, |left, right| left > right).collect())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ConversationExport, ConversationTimeline, DirectMessage};
+    use super::{ExportedMessage, MessageDirection};
+    use crate::auth;
+    use crate::common::tests::load_file;
+
+    const ME: u64 = 2977334326;
+
+    fn load_messages() -> Vec<DirectMessage> {
+        let sample = load_file("sample_payloads/sample-dm.json");
+        ::serde_json::from_str(&sample).unwrap()
+    }
+
+    #[test]
+    fn export_message() {
+        let messages = load_messages();
+
+        let received = ExportedMessage::new(&messages[0], ME);
+        assert_eq!(received.id, 913516532838043652);
+        assert_eq!(received.direction, MessageDirection::Received);
+        assert!(received.urls.is_empty());
+
+        let sent = ExportedMessage::new(&messages[1], ME);
+        assert_eq!(sent.id, 913515749069389828);
+        assert_eq!(sent.direction, MessageDirection::Sent);
+        assert_eq!(
+            sent.urls,
+            vec![
+                "https://github.com/QuietMisdreavus/twitter-rs",
+                "https://t.co/zQz0cWv2pS",
+            ]
+        );
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let mut timeline = ConversationTimeline::new(&auth::Token::Bearer(String::new()));
+        timeline.conversations.insert(126030998, load_messages());
+
+        let export = timeline.export(ME);
+        let value = ::serde_json::to_value(&export).unwrap();
+
+        assert_eq!(
+            value,
+            json!([{
+                "with_user_id": 126030998,
+                "messages": [
+                    {
+                        "id": 913515749069389828u64,
+                        "timestamp": "2017-09-28T21:10:55Z",
+                        "direction": "sent",
+                        "text": "have you seen https://t.co/1xqLfVq8Zp and https://t.co/zQz0cWv2pS",
+                        "urls": [
+                            "https://github.com/QuietMisdreavus/twitter-rs",
+                            "https://t.co/zQz0cWv2pS",
+                        ],
+                    },
+                    {
+                        "id": 913516532838043652u64,
+                        "timestamp": "2017-09-28T21:14:02Z",
+                        "direction": "received",
+                        "text": "oh nice, thanks",
+                        "urls": [],
+                    },
+                ],
+            }])
+        );
+
+        let parsed = ::serde_json::from_value::<Vec<ConversationExport>>(value).unwrap();
+        assert_eq!(parsed, export);
+    }
+}
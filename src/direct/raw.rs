@@ -8,7 +8,7 @@ use crate::user;
 use chrono;
 use serde::Deserialize;
 
-use super::DMEntities;
+use super::{DMEntities, DMReaction};
 
 #[derive(Debug, Deserialize)]
 pub struct RawDirectMessage {
@@ -33,4 +33,13 @@ pub struct RawDirectMessage {
     pub recipient_id: u64,
     ///Full information for the user who received the DM.
     pub recipient: Box<user::TwitterUser>,
+    ///UTC timestamp from when the recipient read this DM, if Twitter included it.
+    ///
+    ///This is only present on payloads from Twitter's newer DM event API, and only if the recipient
+    ///has read receipts turned on.
+    #[serde(default, deserialize_with = "deserialize_opt_timestamp_ms")]
+    pub read_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ///The reactions users have left on this DM. This will be empty if Twitter didn't include any.
+    #[serde(default)]
+    pub reactions: Vec<DMReaction>,
 }
@@ -42,13 +42,19 @@
 //! - `display_url`: This is a truncated version of `expanded_url`, meant to be displayed inline
 //!   with the parent text. This is useful to show users where the link resolves to, without
 //!   potentially filling up a lot of space with the fullly expanded URL.
+//!
+//! `UrlEntity::best_url` picks `expanded_url` if it's present and falls back to `url`. Likewise,
+//! `MentionEntity::profile_url` and `HashtagEntity::search_url` build links to the mentioned
+//! user's profile and the hashtag's page on Twitter.
 use mime;
 use serde::{Deserialize, Deserializer};
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
 
 use crate::common::deserialize_mime;
 
 ///Represents a hashtag or symbol extracted from another piece of text.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
 pub struct HashtagEntity {
     ///The byte offsets where the hashtag is located. The first index is the location of the # or $
     ///character; the second is the location of the first character following the hashtag.
@@ -58,6 +64,19 @@ pub struct HashtagEntity {
     pub text: String,
 }
 
+impl HashtagEntity {
+    ///Returns a link to the page on Twitter for this hashtag.
+    ///
+    ///Note that this is only meaningful for hashtags, not for the symbols (or "cashtags") that are
+    ///also represented by `HashtagEntity`.
+    pub fn search_url(&self) -> String {
+        format!(
+            "https://twitter.com/hashtag/{}",
+            utf8_percent_encode(&self.text, PATH_SEGMENT_ENCODE_SET)
+        )
+    }
+}
+
 ///Represents a piece of media attached to a tweet.
 ///
 ///The information in this struct is subtly different depending on what media is being referenced,
@@ -108,6 +127,29 @@ pub struct MediaEntity {
     pub video_info: Option<VideoInfo>,
     ///Media alt text, if present.
     pub ext_alt_text: Option<String>,
+    ///Whether this media can be viewed, if it was requested. See `MediaAvailability` for details.
+    pub ext_media_availability: Option<MediaAvailability>,
+}
+
+///Information about whether a piece of media is available to view.
+///
+///This is only included in a `MediaEntity` when the call that loaded it asked for it, for example
+///with `SearchBuilder::include_ext_media_availability`. Media can be unavailable if it was removed
+///or withheld, for example.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct MediaAvailability {
+    ///The availability of the media, e.g. `"available"` or `"unavailable"`.
+    pub status: Option<String>,
+    ///If the media is unavailable, the reason why.
+    pub reason: Option<String>,
+}
+
+impl MediaAvailability {
+    ///Returns whether Twitter marked this media as available to view.
+    pub fn is_available(&self) -> bool {
+        self.status.as_ref().map_or(false, |s| s == "available")
+    }
 }
 
 ///Represents the types of media that can be attached to a tweet.
@@ -126,6 +168,7 @@ pub enum MediaType {
 
 ///Represents the available sizes for a media file.
 #[derive(Debug, Copy, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
 pub struct MediaSizes {
     ///Information for a thumbnail-sized version of the media.
     pub thumb: MediaSize,
@@ -150,6 +193,7 @@ pub enum ResizeMode {
 
 ///Represents the dimensions of a media file.
 #[derive(Debug, Copy, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
 pub struct MediaSize {
     ///The size variant's width in pixels.
     pub w: i32,
@@ -161,6 +205,7 @@ pub struct MediaSize {
 
 ///Represents metadata specific to videos.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
 pub struct VideoInfo {
     ///The aspect ratio of the video.
     pub aspect_ratio: (i32, i32),
@@ -174,6 +219,7 @@ pub struct VideoInfo {
 
 ///Represents information about a specific encoding of a video.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
 pub struct VideoVariant {
     ///The bitrate of the video. This value is present for GIFs, but it will be zero.
     pub bitrate: Option<i32>,
@@ -186,6 +232,7 @@ pub struct VideoVariant {
 
 ///Represents a link extracted from another piece of text.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
 pub struct UrlEntity {
     ///A truncated URL meant to be displayed inline with the text.
     #[serde(default)]
@@ -202,6 +249,14 @@ pub struct UrlEntity {
     pub url: String,
 }
 
+impl UrlEntity {
+    ///Returns the URL a link to this entity should point to: `expanded_url` if Twitter gave one,
+    ///otherwise the t.co `url`.
+    pub fn best_url(&self) -> &str {
+        self.expanded_url.as_ref().unwrap_or(&self.url)
+    }
+}
+
 ///Represnts a user mention extracted from another piece of text.
 #[derive(Debug, Clone, Deserialize)]
 pub struct MentionEntity {
@@ -220,6 +275,13 @@ pub struct MentionEntity {
     pub screen_name: String,
 }
 
+impl MentionEntity {
+    ///Returns a link to the mentioned user's profile on Twitter.
+    pub fn profile_url(&self) -> String {
+        format!("https://twitter.com/{}", self.screen_name)
+    }
+}
+
 fn nullable_id<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
     D: Deserializer<'de>,
@@ -22,8 +22,11 @@ use native_tls;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::{self, fmt};
+use std::time::Duration;
 use tokio;
 
+use crate::common::ResponseMeta;
+
 ///Represents a collection of errors returned from a Twitter API call.
 ///
 ///This is returned as part of [`Error::TwitterError`][] whenever Twitter has rejected a call.
@@ -33,6 +36,29 @@ use tokio;
 pub struct TwitterErrors {
     ///A collection of errors returned by Twitter.
     pub errors: Vec<TwitterErrorCode>,
+    ///Diagnostic headers Twitter sent with the errors, if egg-mode's `response-meta` feature is
+    ///enabled. See `ResponseMeta` for details.
+    #[serde(skip)]
+    pub meta: Option<ResponseMeta>,
+}
+
+impl TwitterErrors {
+    ///Returns whether any of these errors has the given error code.
+    ///
+    ///For example, `contains_code(187)` checks whether a tweet was rejected as a duplicate.
+    pub fn contains_code(&self, code: i32) -> bool {
+        self.errors.iter().any(|e| e.code == code)
+    }
+
+    ///Returns an iterator over the error codes of these errors, in the order Twitter gave them.
+    pub fn codes<'a>(&'a self) -> impl Iterator<Item = i32> + 'a {
+        self.errors.iter().map(|e| e.code)
+    }
+
+    ///Returns the message of the first error, if there is one.
+    pub fn first_message(&self) -> Option<&str> {
+        self.errors.first().map(|e| e.message.as_str())
+    }
 }
 
 impl fmt::Display for TwitterErrors {
@@ -72,6 +98,7 @@ impl fmt::Display for TwitterErrorCode {
 
 /// Represents an error that can occur during media processing.
 #[derive(Debug, Clone, PartialEq, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
 pub struct MediaError {
     /// A numeric error code assigned to the error.
     pub code: i32,
@@ -91,6 +118,21 @@ pub enum Error {
     ///
     ///This usually reflects a bug in this library, as it means I'm not parsing input right.
     InvalidResponse(&'static str, Option<String>),
+    ///The media given to a `DraftTweet` can't be attached to a single tweet. The enclosed value
+    ///describes which rule was broken.
+    ///
+    ///Twitter only allows either up to four images, one GIF, or one video to be attached to a
+    ///tweet, and rejects any other combination.
+    InvalidMedia(&'static str),
+    ///A value given to egg-mode couldn't be used as a parameter to Twitter. The enclosed values are
+    ///a description of what was expected and the value that was given.
+    BadParameter(&'static str, String),
+    ///A date given to a search was outside the window of tweets that Twitter's search covers. The
+    ///enclosed value was the date that was given.
+    ///
+    ///The standard search API only covers roughly the last seven days of tweets, so a search
+    ///bounded by an older date would always come back empty.
+    SearchWindow(chrono::NaiveDate),
     ///The response from Twitter was missing an expected value.  The enclosed value was the
     ///expected parameter.
     ///
@@ -103,23 +145,54 @@ pub enum Error {
     ///The response from Twitter returned an error structure instead of the expected response. The
     ///enclosed value was the response from Twitter.
     TwitterError(TwitterErrors),
+    ///The response from Twitter was successful, but contained an error structure alongside the
+    ///data that was loaded. This can happen when only some of the requested items could be
+    ///loaded.
+    ///
+    ///`data_body` is the full response body from Twitter, which can be parsed to load the data
+    ///that was returned. `errors` contains the errors that were returned alongside it.
+    PartialError {
+        ///The full response body from Twitter.
+        data_body: String,
+        ///The errors Twitter returned alongside the data.
+        errors: TwitterErrors,
+    },
     ///The response returned from Twitter contained an error indicating that the rate limit for
-    ///that method has been reached. The enclosed value is the Unix timestamp in UTC when the next
-    ///rate-limit window will open.
-    RateLimit(i32),
+    ///that method has been reached. The enclosed values are the Unix timestamp in UTC when the
+    ///next rate-limit window will open, and the diagnostic headers of the response, if egg-mode's
+    ///`response-meta` feature is enabled.
+    RateLimit(i32, Option<ResponseMeta>),
+    ///Twitter returned HTTP status 420 ("Enhance Your Calm"), meaning the app has connected to the
+    ///Streaming API too often in a short period.
+    ///
+    ///This is different from the regular rate limit: Twitter asks that clients that receive it
+    ///wait at least a minute before reconnecting, and double that wait for each consecutive 420,
+    ///or they risk having their connections blocked for longer.
+    EnhanceYourCalm,
     ///An attempt to upload a video or gif successfully uploaded the file, but failed in
     ///post-processing. The enclosed value contains the error message from Twitter.
     MediaError(MediaError),
-    ///The response from Twitter gave a response code that indicated an error. The enclosed value
-    ///was the response code.
+    ///The response from Twitter gave a response code that indicated an error.
     ///
     ///This is only returned if Twitter did not also return an [error code][TwitterErrors] in the
     ///response body. That check is performed before examining the status code.
     ///
     ///[TwitterErrors]: struct.TwitterErrors.html
-    BadStatus(hyper::StatusCode),
+    BadStatus {
+        ///The response code Twitter returned.
+        status: hyper::StatusCode,
+        ///How long Twitter asked to wait before trying again, if the response included a
+        ///`Retry-After` header. This is usually sent with a 429 or 503 response.
+        retry_after: Option<Duration>,
+        ///Diagnostic headers Twitter sent with the response, if egg-mode's `response-meta`
+        ///feature is enabled.
+        meta: Option<ResponseMeta>,
+    },
     ///The web request experienced an error. The enclosed error was returned from hyper.
     NetError(hyper::error::Error),
+    ///The web request couldn't be assembled, for example because a token contained characters
+    ///that can't be sent in a header. The enclosed error was returned from hyper.
+    RequestBuild(hyper::http::Error),
     ///The `native_tls` implementation returned an error. The enclosed error was returned from
     ///`native_tls`.
     #[cfg(feature = "native_tls")]
@@ -127,9 +200,18 @@ pub enum Error {
     ///An error was experienced while processing the response stream. The enclosed error was
     ///returned from libstd.
     IOError(std::io::Error),
+    ///The response from Twitter wasn't valid UTF-8. The enclosed error was returned from libstd.
+    ///
+    ///This is separate from `IOError` so that a garbled or binary response (for example, an error
+    ///page from a misbehaving proxy) can be told apart from a failure of the connection itself.
+    InvalidUtf8(std::str::Utf8Error),
     ///An error occurred while loading the JSON response. The enclosed error was returned from
     ///`serde_json`.
     DeserializeError(serde_json::Error),
+    ///An error occurred while loading the JSON response, inside a nested value. The enclosed
+    ///values are the path to the value that couldn't be loaded (for example,
+    ///`statuses[3].user.id`) and the error returned from `serde_json`.
+    DeserializeErrorAt(String, serde_json::Error),
     ///An error occurred when parsing a timestamp from Twitter. The enclosed error was returned
     ///from chrono.
     TimestampParseError(chrono::ParseError),
@@ -150,6 +232,23 @@ pub enum Error {
     HeaderConvertError(std::num::ParseIntError),
 }
 
+impl Error {
+    ///Returns the diagnostic headers of the response that caused this error, if the error came
+    ///from Twitter and egg-mode's `response-meta` feature is enabled.
+    ///
+    ///When reporting a failing call to Twitter, they may ask for its `transaction` ID, which this
+    ///gives access to.
+    pub fn meta(&self) -> Option<&ResponseMeta> {
+        match *self {
+            Error::TwitterError(ref errs) => errs.meta.as_ref(),
+            Error::PartialError { ref errors, .. } => errors.meta.as_ref(),
+            Error::RateLimit(_, ref meta) => meta.as_ref(),
+            Error::BadStatus { ref meta, .. } => meta.as_ref(),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
@@ -157,17 +256,31 @@ impl std::fmt::Display for Error {
             Error::InvalidResponse(err, ref ext) => {
                 write!(f, "Invalid response received: {} ({:?})", err, ext)
             }
+            Error::InvalidMedia(err) => write!(f, "Invalid media attachment: {}", err),
+            Error::BadParameter(err, ref val) => write!(f, "Invalid parameter {:?}: {}", val, err),
+            Error::SearchWindow(date) => {
+                write!(f, "Search date {} is outside the last 7 days", date)
+            }
             Error::MissingValue(val) => write!(f, "Value missing from response: {}", val),
             Error::FutureAlreadyCompleted => write!(f, "Future has already been completed"),
             Error::TwitterError(ref err) => write!(f, "Error(s) returned from Twitter: {}", err),
-            Error::RateLimit(ts) => write!(f, "Rate limit reached, hold until {}", ts),
+            Error::PartialError { ref errors, .. } => {
+                write!(f, "Error(s) returned from Twitter alongside data: {}", errors)
+            }
+            Error::RateLimit(ts, _) => write!(f, "Rate limit reached, hold until {}", ts),
+            Error::EnhanceYourCalm => write!(f, "Connected too often, back off before retrying"),
             Error::MediaError(ref err) => write!(f, "Error processing media: {}", err.message),
-            Error::BadStatus(ref val) => write!(f, "Error status received: {}", val),
+            Error::BadStatus { ref status, .. } => write!(f, "Error status received: {}", status),
             Error::NetError(ref err) => write!(f, "Network error: {}", err),
+            Error::RequestBuild(ref err) => write!(f, "Error building request: {}", err),
             #[cfg(feature = "native_tls")]
             Error::TlsError(ref err) => write!(f, "TLS error: {}", err),
             Error::IOError(ref err) => write!(f, "IO error: {}", err),
+            Error::InvalidUtf8(ref err) => write!(f, "Invalid UTF-8 in response: {}", err),
             Error::DeserializeError(ref err) => write!(f, "JSON deserialize error: {}", err),
+            Error::DeserializeErrorAt(ref path, ref err) => {
+                write!(f, "JSON deserialize error at {}: {}", path, err)
+            }
             Error::TimestampParseError(ref err) => write!(f, "Error parsing timestamp: {}", err),
             Error::TimerShutdownError(ref err) => write!(f, "Timer runtime shutdown: {}", err),
             Error::HeaderParseError(ref err) => write!(f, "Error decoding header: {}", err),
@@ -181,17 +294,25 @@ impl std::error::Error for Error {
         match *self {
             Error::BadUrl => "URL given did not match API method",
             Error::InvalidResponse(_, _) => "Invalid response received",
+            Error::InvalidMedia(_) => "Invalid media attachment",
+            Error::BadParameter(_, _) => "Invalid parameter given",
+            Error::SearchWindow(_) => "Search date outside search window",
             Error::MissingValue(_) => "Value missing from response",
             Error::FutureAlreadyCompleted => "Future has already been completed",
             Error::TwitterError(_) => "Error returned from Twitter",
-            Error::RateLimit(_) => "Rate limit for method reached",
+            Error::PartialError { .. } => "Error returned from Twitter alongside data",
+            Error::RateLimit(..) => "Rate limit for method reached",
+            Error::EnhanceYourCalm => "Connection rate limit reached",
             Error::MediaError(_) => "Error processing media",
-            Error::BadStatus(_) => "Response included error code",
+            Error::BadStatus { .. } => "Response included error code",
             Error::NetError(ref err) => err.description(),
+            Error::RequestBuild(ref err) => err.description(),
             #[cfg(feature = "native_tls")]
             Error::TlsError(ref err) => err.description(),
             Error::IOError(ref err) => err.description(),
+            Error::InvalidUtf8(ref err) => err.description(),
             Error::DeserializeError(ref err) => err.description(),
+            Error::DeserializeErrorAt(_, ref err) => err.description(),
             Error::TimestampParseError(ref err) => err.description(),
             Error::TimerShutdownError(ref err) => err.description(),
             Error::HeaderParseError(ref err) => err.description(),
@@ -202,11 +323,14 @@ impl std::error::Error for Error {
     fn cause(&self) -> Option<&dyn std::error::Error> {
         match *self {
             Error::NetError(ref err) => Some(err),
+            Error::RequestBuild(ref err) => Some(err),
             #[cfg(feature = "native_tls")]
             Error::TlsError(ref err) => Some(err),
             Error::IOError(ref err) => Some(err),
+            Error::InvalidUtf8(ref err) => Some(err),
             Error::TimestampParseError(ref err) => Some(err),
             Error::DeserializeError(ref err) => Some(err),
+            Error::DeserializeErrorAt(_, ref err) => Some(err),
             Error::TimerShutdownError(ref err) => Some(err),
             Error::HeaderParseError(ref err) => Some(err),
             Error::HeaderConvertError(ref err) => Some(err),
@@ -221,6 +345,12 @@ impl From<hyper::error::Error> for Error {
     }
 }
 
+impl From<hyper::http::Error> for Error {
+    fn from(err: hyper::http::Error) -> Error {
+        Error::RequestBuild(err)
+    }
+}
+
 #[cfg(feature = "native_tls")]
 impl From<native_tls::Error> for Error {
     fn from(err: native_tls::Error) -> Error {
@@ -234,6 +364,18 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<std::str::Utf8Error> for Error {
+    fn from(err: std::str::Utf8Error) -> Error {
+        Error::InvalidUtf8(err)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(err: std::string::FromUtf8Error) -> Error {
+        Error::InvalidUtf8(err.utf8_error())
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Error {
         Error::DeserializeError(err)
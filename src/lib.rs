@@ -72,9 +72,14 @@
 //!
 //! `Response` also has IntoIterator implementations and iterator creation methods that echo those
 //! on `Vec<T>`, for methods that return Vecs. These methods and iterator types distribute the
-//! rate-limit information across each iteration.
+//! rate-limit information across each iteration. To run several calls at once and gather their
+//! results into one `Response`, use [`run_bounded`][]. To watch the rate-limit information of
+//! every call in one place, e.g. to export it as metrics, set a hook with `on_rate_limit`. To
+//! retry failed calls or reconnect streams, `Backoff` gives the delays Twitter asks clients to
+//! wait between attempts.
 //!
 //! [`Response`]: struct.Response.html
+//! [`run_bounded`]: fn.run_bounded.html
 //!
 //! ## `TwitterFuture<'a, T>`
 //!
@@ -94,7 +99,8 @@
 //!
 //! The remaining types and methods are explained as part of the [authentication overview][Token],
 //! with the exception of `verify_tokens`, which is a simple method to ensure a given token is
-//! still valid.
+//! still valid, `set_user_agent`, which changes the `User-Agent` header egg-mode sends with its
+//! requests, and `AutoBearer`, which loads and saves a Bearer token for you.
 //!
 //! # Modules
 //!
@@ -119,6 +125,7 @@
 //!   removing users, or loading the posts made by their members.
 //! * `media`: This module lets you upload images, GIFs, and videos to Twitter so you can attach
 //!   them to tweets.
+//! * `account`: This module lets you manage the authenticated user's own profile.
 //!
 //! ## Secondary actions
 //!
@@ -130,6 +137,8 @@
 //! * `service`: These are some miscellaneous methods that show information about the Twitter
 //!   service as a whole, like loading the maximum length of t.co URLs or loading the current Terms
 //!   of Service or Privacy Policy.
+//! * `trends`: This module loads the current trending topics for a location, and lets you cache
+//!   them so they aren't loaded more often than they change.
 //!
 //! ## Helper structs
 //!
@@ -143,6 +152,8 @@
 //! * `error`: Any interaction with Twitter may result in an error condition, be it from finding a
 //!   tweet or user that doesn't exist or the network connection being unavailable. All the error
 //!   types are aggregated into an enum in this module.
+//! * `prelude`: This re-exports the most commonly used types and functions from the other modules,
+//!   so they can all be imported at once with `use egg_mode::prelude::*;`.
 
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
@@ -154,16 +165,21 @@
 // extern crate serde_derive;
 
 pub use crate::auth::{
-    access_token, authenticate_url, authorize_url, bearer_token, invalidate_bearer, KeyPair,
-    request_token, Token, verify_tokens,
+    access_token, AutoBearer, authenticate_url, authorize_url, bearer_token, invalidate_bearer,
+    KeyPair, request_token, request_token_oob, set_user_agent, Token, verify_tokens,
 };
+#[cfg(feature = "test-util")]
+pub use crate::auth::{sign_request_debug, SignatureDebug};
 pub use crate::common::{
-    FutureResponse, Response, ResponseIter, ResponseIterMut, ResponseIterRef, TwitterFuture,
+    Backoff, BackoffKind, BytesStream, clear_rate_limit_hook, FutureResponse, on_rate_limit,
+    IndependentResults, parse_query, RateLimit, Response, ResponseIter, ResponseIterMut,
+    ResponseIterRef, ResponseMeta, run_bounded, TwitterFuture, WithRaw,
 };
 
 #[macro_use]
 mod common;
 mod auth;
+pub mod account;
 pub mod cursor;
 pub mod direct;
 pub mod entities;
@@ -172,9 +188,11 @@ mod links;
 pub mod list;
 pub mod media;
 pub mod place;
+pub mod prelude;
 pub mod search;
 pub mod service;
 pub mod stream;
+pub mod trends;
 pub mod tweet;
 pub mod user;
 
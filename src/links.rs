@@ -13,6 +13,13 @@ pub mod auth {
         "https://api.twitter.com/1.1/account/verify_credentials.json";
 }
 
+pub mod account {
+    pub const REMOVE_PROFILE_BANNER: &'static str =
+        "https://api.twitter.com/1.1/account/remove_profile_banner.json";
+    pub const UPDATE_PROFILE_COLORS: &'static str =
+        "https://api.twitter.com/1.1/account/update_profile_colors.json";
+}
+
 pub mod users {
     pub const LOOKUP: &'static str = "https://api.twitter.com/1.1/users/lookup.json";
     pub const SHOW: &'static str = "https://api.twitter.com/1.1/users/show.json";
@@ -43,6 +50,10 @@ pub mod users {
     pub const REPORT_SPAM: &'static str = "https://api.twitter.com/1.1/users/report_spam.json";
     pub const MUTE: &'static str = "https://api.twitter.com/1.1/mutes/users/create.json";
     pub const UNMUTE: &'static str = "https://api.twitter.com/1.1/mutes/users/destroy.json";
+    pub const PROFILE_BANNER: &'static str =
+        "https://api.twitter.com/1.1/users/profile_banner.json";
+    pub const SUGGESTIONS: &'static str = "https://api.twitter.com/1.1/users/suggestions.json";
+    pub const SUGGESTIONS_STEM: &'static str = "https://api.twitter.com/1.1/users/suggestions";
 }
 
 pub mod statuses {
@@ -67,6 +78,10 @@ pub mod statuses {
     pub const UNLIKE: &'static str = "https://api.twitter.com/1.1/favorites/destroy.json";
     pub const UPDATE: &'static str = "https://api.twitter.com/1.1/statuses/update.json";
     pub const DELETE_STEM: &'static str = "https://api.twitter.com/1.1/statuses/destroy";
+    pub const MUTE_CONVERSATION: &'static str =
+        "https://api.twitter.com/1.1/mutes/conversations/create.json";
+    pub const UNMUTE_CONVERSATION: &'static str =
+        "https://api.twitter.com/1.1/mutes/conversations/destroy.json";
 }
 
 pub mod media {
@@ -105,6 +120,8 @@ pub mod place {
     pub const REVERSE_GEOCODE: &'static str =
         "https://api.twitter.com/1.1/geo/reverse_geocode.json";
     pub const SEARCH: &'static str = "https://api.twitter.com/1.1/geo/search.json";
+    pub const SIMILAR_PLACES: &'static str =
+        "https://api.twitter.com/1.1/geo/similar_places.json";
 }
 
 pub mod direct {
@@ -113,6 +130,10 @@ pub mod direct {
     pub const SENT: &'static str = "https://api.twitter.com/1.1/direct_messages/sent.json";
     pub const SEND: &'static str = "https://api.twitter.com/1.1/direct_messages/new.json";
     pub const DELETE: &'static str = "https://api.twitter.com/1.1/direct_messages/destroy.json";
+    pub const MARK_READ: &'static str =
+        "https://api.twitter.com/1.1/direct_messages/mark_read.json";
+    pub const INDICATE_TYPING: &'static str =
+        "https://api.twitter.com/1.1/direct_messages/indicate_typing.json";
 }
 
 pub mod service {
@@ -123,6 +144,11 @@ pub mod service {
         "https://api.twitter.com/1.1/application/rate_limit_status.json";
 }
 
+pub mod trends {
+    pub const PLACE: &'static str = "https://api.twitter.com/1.1/trends/place.json";
+    pub const CLOSEST: &'static str = "https://api.twitter.com/1.1/trends/closest.json";
+}
+
 pub mod stream {
     pub const SAMPLE: &'static str = "https://stream.twitter.com/1.1/statuses/sample.json";
     pub const FILTER: &'static str = "https://stream.twitter.com/1.1/statuses/filter.json";
@@ -123,27 +123,7 @@ pub fn is_subscribed<'id, T: Into<UserID<'id>>>(
 
     let req = auth::get(links::lists::IS_SUBSCRIBER, token, Some(&params));
 
-    fn parse_resp(full_resp: String, headers: &Headers) -> Result<Response<bool>, error::Error> {
-        let out: WebResponse<TwitterUser> = make_response(full_resp, headers);
-
-        match out {
-            Ok(user) => Ok(Response::map(user, |_| true)),
-            Err(TwitterError(terrs)) => {
-                if terrs.errors.iter().any(|e| e.code == 109) {
-                    //here's a fun conundrum: since "is not in this list" is returned as an error code,
-                    //the rate limit info that would otherwise be part of the response isn't there. the
-                    //rate_headers method was factored out specifically for this location, since it's
-                    //still there, just accompanying an error response instead of a user.
-                    Ok(Response::map(rate_headers(headers)?, |_| false))
-                } else {
-                    Err(TwitterError(terrs))
-                }
-            }
-            Err(err) => Err(err),
-        }
-    }
-
-    make_future(req, parse_resp)
+    make_future_with_errors(req, parse_user_in_list)
 }
 
 ///Check whether the given user has been added to the given list.
@@ -159,27 +139,32 @@ pub fn is_member<'id, T: Into<UserID<'id>>>(
 
     let req = auth::get(links::lists::IS_MEMBER, token, Some(&params));
 
-    fn parse_resp(full_resp: String, headers: &Headers) -> Result<Response<bool>, error::Error> {
-        let out: WebResponse<TwitterUser> = make_response(full_resp, headers);
-
-        match out {
-            Ok(user) => Ok(Response::map(user, |_| true)),
-            Err(TwitterError(terrs)) => {
-                if terrs.errors.iter().any(|e| e.code == 109) {
-                    //here's a fun conundrum: since "is not in this list" is returned as an error code,
-                    //the rate limit info that would otherwise be part of the response isn't there. the
-                    //rate_headers method was factored out specifically for this location, since it's
-                    //still there, just accompanying an error response instead of a user.
-                    Ok(Response::map(rate_headers(headers)?, |_| false))
-                } else {
-                    Err(TwitterError(terrs))
-                }
+    make_future_with_errors(req, parse_user_in_list)
+}
+
+///Parses the response from `is_subscribed` or `is_member`: a user if they're in the list, or an
+///error with code 109 if they aren't.
+pub(super) fn parse_user_in_list(
+    full_resp: String,
+    headers: &Headers,
+) -> Result<Response<bool>, error::Error> {
+    let out: WebResponse<TwitterUser> = make_response(full_resp, headers);
+
+    match out {
+        Ok(user) => Ok(Response::map(user, |_| true)),
+        Err(TwitterError(terrs)) => {
+            if terrs.contains_code(109) {
+                //here's a fun conundrum: since "is not in this list" is returned as an error code,
+                //the rate limit info that would otherwise be part of the response isn't there. the
+                //rate_headers method was factored out specifically for this location, since it's
+                //still there, just accompanying an error response instead of a user.
+                Ok(Response::map(rate_headers(headers)?, |_| false))
+            } else {
+                Err(TwitterError(terrs))
             }
-            Err(err) => Err(err),
         }
+        Err(err) => Err(err),
     }
-
-    make_future(req, parse_resp)
 }
 
 ///Begin navigating the collection of tweets made by the users added to the given list.
@@ -249,6 +249,8 @@ impl<'a> ListUpdate<'a> {
 mod tests {
     use super::List;
     use crate::common::tests::load_file;
+    use crate::common::Headers;
+    use crate::error;
 
     #[test]
     fn parse_list_sample() {
@@ -257,4 +259,23 @@ mod tests {
         assert_eq!(list.full_name, "@Scobleizer/all-people-in-spatial-2");
         assert_eq!(list.user.screen_name, "Scobleizer")
     }
+
+    #[test]
+    fn parse_not_in_list() {
+        let mut headers = Headers::new();
+        headers.insert("x-rate-limit-limit", "75".parse().unwrap());
+        headers.insert("x-rate-limit-remaining", "74".parse().unwrap());
+        headers.insert("x-rate-limit-reset", "1570000000".parse().unwrap());
+
+        let body = r#"{"errors":[{"code":109,"message":"User is not in this list."}]}"#;
+        let resp = super::parse_user_in_list(body.to_string(), &headers).unwrap();
+        assert!(!resp.response);
+        assert_eq!(resp.rate_limit_remaining, 74);
+
+        let body = r#"{"errors":[{"code":34,"message":"Sorry, that page does not exist."}]}"#;
+        match super::parse_user_in_list(body.to_string(), &headers) {
+            Err(error::Error::TwitterError(errs)) => assert!(errs.contains_code(34)),
+            other => panic!("expected a TwitterError, got {:?}", other),
+        }
+    }
 }
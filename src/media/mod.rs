@@ -35,6 +35,15 @@
 //! ```
 //!
 //! For more information, see the [`UploadBuilder`] documentation.
+//!
+//! To load media that's already attached to a tweet or direct message, use [`download`].
+//!
+//! [`download`]: fn.download.html
+//!
+//! `UploadFuture` waits for uploaded video and GIFs to finish processing on its own, but if you
+//! need to check on media that was uploaded some other way, use [`upload_status`].
+//!
+//! [`upload_status`]: fn.upload_status.html
 
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -153,6 +162,109 @@ impl<'de> Deserialize<'de> for ProgressInfo {
     }
 }
 
+/// Download the media file at the given URL, authenticating the request with the given token.
+///
+/// This is meant for media URLs given in [`MediaEntity`] and [`VideoVariant`]. Public media can be
+/// loaded without authentication, but media attached to direct messages needs the request to be
+/// signed by one of the participants, which this takes care of.
+///
+/// Since media files can be large, the returned [`BytesStream`] yields the file in chunks as they
+/// arrive instead of loading the whole file into memory.
+///
+/// [`MediaEntity`]: ../entities/struct.MediaEntity.html
+/// [`VideoVariant`]: ../entities/struct.VideoVariant.html
+/// [`BytesStream`]: ../struct.BytesStream.html
+pub fn download(url: &str, token: &auth::Token) -> BytesStream {
+    make_bytes_stream(auth::get(url, token, None))
+}
+
+/// Load the processing status of the uploaded media with the given ID.
+///
+/// Videos and GIFs are processed by Twitter after they're uploaded, and can't be attached to a
+/// tweet until processing succeeds. `UploadFuture` already waits for this, so this is only needed
+/// when checking on media that was uploaded some other way. Media that doesn't need processing
+/// (like images) is reported as `ProcessingState::Succeeded`.
+///
+/// If the returned state is `Pending` or `InProgress`, Twitter asks that you wait for
+/// `check_after_secs` before checking again.
+pub fn upload_status(media_id: u64, token: &auth::Token) -> FutureResponse<MediaStatus> {
+    let mut params = HashMap::new();
+
+    add_param(&mut params, "command", "STATUS");
+    add_param(&mut params, "media_id", media_id.to_string());
+
+    let req = auth::get(links::media::UPLOAD, token, Some(&params));
+    make_parsed_future(req)
+}
+
+/// The processing status of uploaded media, as returned by [`upload_status`].
+///
+/// [`upload_status`]: fn.upload_status.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaStatus {
+    /// The current processing state of the media.
+    pub state: ProcessingState,
+    /// If processing isn't finished, the number of seconds to wait before checking again.
+    pub check_after_secs: Option<u32>,
+    /// If given, how far along processing is, as a percentage from 0 to 100.
+    pub progress_percent: Option<u8>,
+    /// If processing failed, the reason why.
+    pub error: Option<error::MediaError>,
+}
+
+/// The processing state of uploaded media.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub enum ProcessingState {
+    /// The media is waiting to be processed.
+    #[serde(rename = "pending")]
+    Pending,
+    /// The media is being processed.
+    #[serde(rename = "in_progress")]
+    InProgress,
+    /// The media was processed, and can be attached to a tweet.
+    #[serde(rename = "succeeded")]
+    Succeeded,
+    /// The media couldn't be processed. The reason is given in `MediaStatus::error`.
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawMediaStatus {
+    processing_info: Option<RawStatusInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawStatusInfo {
+    state: ProcessingState,
+    check_after_secs: Option<u32>,
+    progress_percent: Option<f64>,
+    error: Option<error::MediaError>,
+}
+
+impl<'de> Deserialize<'de> for MediaStatus {
+    fn deserialize<D>(deser: D) -> Result<MediaStatus, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawMediaStatus::deserialize(deser)?;
+        Ok(match raw.processing_info {
+            Some(info) => MediaStatus {
+                state: info.state,
+                check_after_secs: info.check_after_secs,
+                progress_percent: info.progress_percent.map(|p| p.max(0.0).min(100.0) as u8),
+                error: info.error,
+            },
+            None => MediaStatus {
+                state: ProcessingState::Succeeded,
+                check_after_secs: None,
+                progress_percent: None,
+                error: None,
+            },
+        })
+    }
+}
+
 /// A media handle returned by twitter upon successful upload.
 ///
 /// To get one of these, start with [`UploadBuilder`]. To use the `id` inside, see
@@ -164,6 +276,10 @@ impl<'de> Deserialize<'de> for ProgressInfo {
 pub struct MediaHandle {
     /// The ID that can be used to reference the media.
     pub id: u64,
+    /// The kind of media that was uploaded.
+    pub category: MediaCategory,
+    /// The size of the uploaded media, in bytes.
+    pub size: u64,
     /// The time after which the media will be rendered unusable in the twitter API.
     pub valid_until: Instant,
 }
@@ -195,17 +311,25 @@ struct RawMedia {
 }
 
 impl RawMedia {
-    fn into_handle(self) -> MediaHandle {
+    fn into_handle(self, category: MediaCategory, size: u64) -> MediaHandle {
         MediaHandle {
             id: self.id,
+            category: category,
+            size: size,
             valid_until: Instant::now() + Duration::from_secs(self.expires_after),
         }
     }
 }
 
-/// Represents the kinda of media that Twitter will accept.
+/// Represents the kinds of media that Twitter will accept.
+///
+/// This is set automatically by [`UploadBuilder::new`] based on the media type of the upload, and
+/// is given back as part of the resulting [`MediaHandle`].
+///
+/// [`UploadBuilder::new`]: struct.UploadBuilder.html#method.new
+/// [`MediaHandle`]: struct.MediaHandle.html
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum MediaCategory {
+pub enum MediaCategory {
     /// Static image. Four can be attached to a single tweet.
     Image,
     /// Animated GIF.
@@ -594,7 +718,8 @@ impl<'a> Future for UploadFuture<'a> {
                     Poll::Ready(Ok(media)) => {
                         if media.progress.is_none() || media.progress == Some(ProgressInfo::Success)
                         {
-                            let media = media.response.into_handle();
+                            let size = mut_self.data.len() as u64;
+                            let media = media.response.into_handle(mut_self.media_category, size);
                             mut_self.timeout = media.valid_until;
                             let loader = mut_self.alt_text.as_ref().map(|txt| mut_self.metadata(id, txt));
                             if let Some(loader) = loader {
@@ -805,7 +930,7 @@ impl StdError for UploadError {
 mod tests {
     use crate::common::tests::load_file;
 
-    use super::RawMedia;
+    use super::{MediaStatus, ProcessingState, RawMedia};
 
     fn load_media(path: &str) -> RawMedia {
         let content = load_file(path);
@@ -868,4 +993,20 @@ mod tests {
             other => assert!(false, format!("Unexpected value of progress={:?}", other)),
         }
     }
+
+    #[test]
+    fn parse_media_status() {
+        let content = load_file("sample_payloads/media_in_progress.json");
+        let status = ::serde_json::from_str::<MediaStatus>(&content).unwrap();
+
+        assert_eq!(status.state, ProcessingState::InProgress);
+        assert_eq!(status.check_after_secs, Some(10));
+        assert_eq!(status.progress_percent, Some(8));
+        assert!(status.error.is_none());
+
+        let content = load_file("sample_payloads/media.json");
+        let status = ::serde_json::from_str::<MediaStatus>(&content).unwrap();
+
+        assert_eq!(status.state, ProcessingState::Succeeded);
+    }
 }
@@ -2,13 +2,13 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::collections::HashMap;
-
 use futures_core::Future;
+use futures_util::future::{self, Either};
+use futures_util::TryFutureExt;
 
-use crate::{auth, error, links};
+use crate::{auth, error, links, trends};
 use crate::common::*;
-use crate::error::Error::BadUrl;
+use crate::error::Error::{BadUrl, InvalidResponse, MissingValue};
 
 use super::*;
 use super::PlaceQuery;
@@ -69,18 +69,7 @@ fn parse_url<'a>(base: &'static str, full: &'a str) -> Result<ParamList<'a>, err
     }
 
     if let Some(list) = iter.next() {
-        let mut p = HashMap::new();
-
-        for item in list.split('&') {
-            let mut kv_iter = item.split('=');
-
-            let k = kv_iter.next().ok_or(BadUrl)?;
-            let v = kv_iter.next().ok_or(BadUrl)?;
-
-            add_param(&mut p, k, v);
-        }
-
-        Ok(p)
+        parse_query(list)
     } else {
         Err(BadUrl)
     }
@@ -96,10 +85,66 @@ pub fn reverse_geocode_url<'a>(
     url: &'a str,
     token: &'a auth::Token,
 ) -> impl Future<Output = Result<Response<SearchResult>, error::Error>> + 'a {
-    // TODO handle error
-    let params = parse_url(links::place::REVERSE_GEOCODE, url).unwrap();
-    let req = auth::get(links::place::REVERSE_GEOCODE, &token, Some(&params));
-    make_parsed_future(req)
+    match parse_url(links::place::REVERSE_GEOCODE, url) {
+        Ok(params) => {
+            let req = auth::get(links::place::REVERSE_GEOCODE, &token, Some(&params));
+            Either::Left(make_parsed_future(req))
+        }
+        Err(e) => Either::Right(future::ready(Err(e))),
+    }
+}
+
+///The number of reverse-geocode calls `reverse_geocode_batch` keeps in flight at once.
+const BATCH_CONCURRENCY: usize = 4;
+
+/// Reverse-geocodes each of the given `(latitude, longitude)` points with the given granularity.
+///
+/// The calls are made with `run_bounded`, so only a few of them are in flight at once. Each result
+/// is paired with the index of its point in `points`, and the results are returned in the same
+/// order as the points. Each point counts against the rate limit for `reverse_geocode` like a
+/// regular call, so to avoid looking up the same area twice, consider checking a `GeoCache` for
+/// each point before handing the rest to this function.
+///
+/// If any call returns an error, that error is returned and the remaining calls are dropped.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// use tokio::runtime::current_thread::block_on_all;
+/// # fn main() {
+/// # let token: Token = unimplemented!();
+/// use egg_mode::place::{self, PlaceType};
+/// let points = [(51.507222, -0.1275), (48.8567, 2.3508)];
+/// let results = block_on_all(place::reverse_geocode_batch(&points, PlaceType::City, &token))
+///                   .unwrap();
+///
+/// for (idx, result) in results {
+///     println!("{:?}: {:?}", points[idx], result.results.first().map(|pl| &pl.full_name));
+/// }
+/// # }
+/// ```
+pub fn reverse_geocode_batch(
+    points: &[(f64, f64)],
+    granularity: PlaceType,
+    token: &auth::Token,
+) -> impl Future<Output = Result<Vec<(usize, SearchResult)>, error::Error>> {
+    let calls = points
+        .iter()
+        .enumerate()
+        .map(|(idx, &(latitude, longitude))| {
+            reverse_geocode(latitude, longitude)
+                .granularity(granularity)
+                .call(token)
+                .map_ok(move |resp| Response::map(resp, move |result| (idx, result)))
+        })
+        .collect::<Vec<_>>();
+
+    run_bounded(calls, BATCH_CONCURRENCY).map_ok(|resp| {
+        let mut results = resp.response;
+        results.sort_by_key(|&(idx, _)| idx);
+        results
+    })
 }
 
 /// Begins building a location search via latitude/longitude.
@@ -151,6 +196,15 @@ pub fn search_ip(query: &str) -> SearchBuilder {
     SearchBuilder::new(PlaceQuery::IPAddress(query))
 }
 
+///Begins building a search for places similar to a new place with the given name and location.
+///
+///This uses the same builder as the `search_*` functions, and takes the same optional
+///parameters. Along with the list of similar places, the result includes a `token` that Twitter
+///requires when creating a new place, in case none of the results match.
+pub fn similar_places(latitude: f64, longitude: f64, name: &str) -> SearchBuilder {
+    SearchBuilder::new(PlaceQuery::Similar(latitude, longitude, name))
+}
+
 ///From a URL given with the result of any `search_*` function, perform the same location search.
 ///
 ///## Errors
@@ -161,7 +215,47 @@ pub fn search_url<'a>(
     url: &'a str,
     token: &'a auth::Token,
 ) -> impl Future<Output = Result<Response<SearchResult>, error::Error>> + 'a {
-    let params = parse_url(links::place::SEARCH, url).unwrap();
-    let req = auth::get(links::place::REVERSE_GEOCODE, &token, Some(&params));
-    make_parsed_future(req)
+    match parse_url(links::place::SEARCH, url) {
+        Ok(params) => {
+            let req = auth::get(links::place::SEARCH, &token, Some(&params));
+            Either::Left(make_parsed_future(req))
+        }
+        Err(e) => Either::Right(future::ready(Err(e))),
+    }
+}
+
+///Load the current trending topics for the given place.
+///
+///If the place has a WOEID in its `attributes` (see `Place::woeid`), this loads the trends for it
+///directly. Otherwise, this calls `trends::closest` with the center of the place's bounding box,
+///and loads the trends for the closest location Twitter has trends for. Note that this can be a
+///much larger area than the place itself, since Twitter only tracks trends for some cities and
+///countries.
+///
+///## Errors
+///
+///In addition to errors that might occur generally, this function will return a `MissingValue`
+///error if the place has neither a WOEID nor a bounding box.
+pub fn trends_for(
+    place: &Place,
+    token: &auth::Token,
+) -> impl Future<Output = Result<Response<trends::TrendsResult>, error::Error>> {
+    let woeid = if let Some(woeid) = place.woeid() {
+        Either::Left(future::ready(Ok(woeid)))
+    } else if let Some((lat, lon)) = place.centroid() {
+        Either::Right(trends::closest(lat, lon, token).and_then(|locations| {
+            future::ready(
+                locations
+                    .response
+                    .first()
+                    .map(|loc| loc.woeid)
+                    .ok_or(InvalidResponse("no trend locations near place", None)),
+            )
+        }))
+    } else {
+        Either::Left(future::ready(Err(MissingValue("bounding_box"))))
+    };
+
+    let token = token.clone();
+    woeid.and_then(move |woeid| trends::place(woeid, &token))
 }
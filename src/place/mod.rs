@@ -25,16 +25,35 @@
 //! Along with the list of place results, Twitter also returns the full search URL. egg-mode
 //! returns this URL as part of the result struct, allowing you to perform the same search using
 //! the `reverse_geocode_url` or `search_url` functions.
+//!
+//! To check whether a place already exists before creating a new one, use `similar_places`. Its
+//! result includes a `token` that Twitter requires when creating a place with `geo/place.json`.
+//! egg-mode doesn't wrap that call yet, but the token is passed through so you can make it
+//! yourself.
+//!
+//! To load the trending topics near a place, use `trends_for`, which finds the place's WOEID (see
+//! the [`trends`][] module) before loading its trends.
+//!
+//! [`trends`]: ../trends/index.html
+//!
+//! Reverse-geocoding nearby points usually returns the same places, so when looking up many points
+//! at once, a `GeoCache` can be given to `GeocodeBuilder::call_cached` to reuse the results for
+//! points that round to the same coordinate. To look up a batch of points that aren't cached,
+//! `reverse_geocode_batch` runs their calls a few at a time and returns the results in order.
 
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::fmt;
 
+use futures_core::Future;
+use futures_util::future::{self, Either};
+use futures_util::FutureExt;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json;
 
 use crate::common::*;
-use crate::{auth, links};
+use crate::{auth, error, links};
 
 mod fun;
 
@@ -57,6 +76,9 @@ pub struct Place {
     ///Name of the country containing this place.
     pub country: String,
     ///Shortened country code representing the country containing this place.
+    ///
+    ///This is the ISO 3166-1 alpha-2 code of the country, but may be empty; see
+    ///`iso_country_code` for a version that returns `None` in that case.
     pub country_code: String,
     ///Full human-readable name of this place.
     pub full_name: String,
@@ -68,8 +90,60 @@ pub struct Place {
     pub contained_within: Option<Vec<Place>>,
 }
 
+impl Place {
+    ///Returns the Yahoo! "Where On Earth" ID (WOEID) of this place, if Twitter included one in its
+    ///`attributes`.
+    ///
+    ///Most places don't include this; see `trends_for` for a way to find trends for a place
+    ///without it.
+    pub fn woeid(&self) -> Option<u32> {
+        self.attributes.get("woeid")?.parse().ok()
+    }
+
+    ///Returns the ISO 3166-1 alpha-2 code of the country containing this place, e.g. `"US"`.
+    ///
+    ///This is the same as `country_code`, but returns `None` if Twitter left it empty, which it
+    ///does for some places that aren't in any one country.
+    pub fn iso_country_code(&self) -> Option<&str> {
+        Some(self.country_code.as_str()).filter(|code| !code.is_empty())
+    }
+
+    ///Returns the ISO 3166-1 alpha-3 code of the country containing this place, e.g. `"USA"`, if
+    ///Twitter included one in its `attributes`.
+    pub fn iso3(&self) -> Option<&str> {
+        self.attributes
+            .get("iso3")
+            .map(|code| code.as_str())
+            .filter(|code| !code.is_empty())
+    }
+
+    ///Returns the center of this place's bounding box, as a (latitude, longitude) pair, or `None`
+    ///if the bounding box is empty.
+    ///
+    ///Note that this is the opposite order from the points in `bounding_box`, which are given in
+    ///(longitude, latitude) order.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        if self.bounding_box.is_empty() {
+            return None;
+        }
+
+        let count = self.bounding_box.len() as f64;
+        let (lon_sum, lat_sum) = self
+            .bounding_box
+            .iter()
+            .fold((0.0, 0.0), |(lon_sum, lat_sum), &(lon, lat)| {
+                (lon_sum + lon, lat_sum + lat)
+            });
+
+        Some((lat_sum / count, lon_sum / count))
+    }
+}
+
 ///Represents the type of region represented by a given place.
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+///
+///`PlaceType`s are ordered by how large of an area they represent, so `PointOfInterest` is the
+///smallest and `Country` is the largest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum PlaceType {
     ///A coordinate with no area.
     #[serde(rename = "poi")]
@@ -97,13 +171,37 @@ pub enum Accuracy {
     Feet(f64),
 }
 
-///Represents the result of a location search, either via `reverse_geocode` or `search`.
+///Represents the result of a location search, either via `reverse_geocode`, `search`, or
+///`similar_places`.
+#[derive(Debug, Clone)]
 pub struct SearchResult {
-    ///The full URL used to pull the result list. This can be fed to the `_url` version of your
-    ///original call to avoid having to fill out the argument list again.
+    ///The full URL used to pull the result list, as given in the `query.url` field of Twitter's
+    ///response. This can be saved and later fed to the `_url` version of your original call (i.e.
+    ///`reverse_geocode_url` or `search_url`) to avoid having to fill out the argument list again.
     pub url: String,
     ///The list of results from the search.
     pub results: Vec<Place>,
+    ///The place-creation token returned by `similar_places`, or `None` for other searches.
+    ///
+    ///Twitter requires this token when creating a new place with `geo/place.json`, to show that
+    ///the similar places were checked first.
+    pub token: Option<String>,
+}
+
+impl SearchResult {
+    ///Returns the most specific place in the results, i.e. the one with the smallest `place_type`.
+    ///
+    ///If several places have the same `place_type`, the first one in the results is returned.
+    pub fn most_specific(&self) -> Option<&Place> {
+        self.results.iter().min_by_key(|place| place.place_type)
+    }
+
+    ///Returns the least specific place in the results, i.e. the one with the largest `place_type`.
+    ///
+    ///If several places have the same `place_type`, the first one in the results is returned.
+    pub fn least_specific(&self) -> Option<&Place> {
+        self.results.iter().min_by_key(|place| Reverse(place.place_type))
+    }
 }
 
 impl<'de> Deserialize<'de> for SearchResult {
@@ -115,6 +213,7 @@ impl<'de> Deserialize<'de> for SearchResult {
         let url = raw
             .get("query")
             .and_then(|obj| obj.get("url"))
+            .and_then(|url| url.as_str())
             .ok_or_else(|| D::Error::custom("Malformed search result"))?
             .to_string();
         let results = raw
@@ -122,7 +221,16 @@ impl<'de> Deserialize<'de> for SearchResult {
             .and_then(|obj| obj.get("places"))
             .and_then(|arr| <Vec<Place>>::deserialize(arr).ok())
             .ok_or_else(|| D::Error::custom("Malformed search result"))?;
-        Ok(SearchResult { url, results })
+        let token = raw
+            .get("result")
+            .and_then(|obj| obj.get("token"))
+            .and_then(|token| token.as_str())
+            .map(|token| token.to_string());
+        Ok(SearchResult {
+            url,
+            results,
+            token,
+        })
     }
 }
 
@@ -166,6 +274,9 @@ impl GeocodeBuilder {
 
     ///Sets the minimal specificity of what kind of results to return. For example, passing `City`
     ///to this will make the eventual result exclude neighborhoods and points.
+    ///
+    ///By default, no granularity is sent, and Twitter returns a spread of results of different
+    ///kinds, starting from the most specific. Use `clear_granularity` to go back to that.
     pub fn granularity(self, granularity: PlaceType) -> Self {
         GeocodeBuilder {
             granularity: Some(granularity),
@@ -173,6 +284,15 @@ impl GeocodeBuilder {
         }
     }
 
+    ///Clears a granularity set with `granularity`, so Twitter's default set of results is
+    ///returned.
+    pub fn clear_granularity(self) -> Self {
+        GeocodeBuilder {
+            granularity: None,
+            ..self
+        }
+    }
+
     ///Restricts the maximum number of results returned in this search. This is not a guarantee
     ///that the search will return this many results, but instead provides a hint as to how many
     ///"nearby" results to return.
@@ -213,12 +333,134 @@ impl GeocodeBuilder {
 
         make_parsed_future(req)
     }
+
+    ///Finalize the search parameters and return the results collection, reusing the results saved
+    ///in the given cache if this coordinate has been looked up before.
+    ///
+    ///The cache is keyed on the coordinate, rounded to the cache's precision, and the
+    ///`granularity` set on this builder. If the results aren't in the cache, this calls `call`
+    ///and saves the results into the cache before returning them.
+    ///
+    ///`accuracy` and `max_results` aren't part of the key, so the cache is only used when they're
+    ///left at their defaults. If either of them is set to something else, this works like `call`,
+    ///and neither reads from nor saves into the cache.
+    ///
+    ///Since cached results don't come from a network call, this future doesn't return rate-limit
+    ///information like `call` does.
+    pub fn call_cached<'c>(
+        &self,
+        cache: &'c mut GeoCache,
+        token: &auth::Token,
+    ) -> impl Future<Output = Result<SearchResult, error::Error>> + 'c {
+        let (lat, lon) = self.coordinate;
+        let granularity = self.granularity;
+        let cache = if self.has_default_options() { Some(cache) } else { None };
+
+        if let Some(results) = cache
+            .as_ref()
+            .and_then(|cache| cache.get(lat, lon, granularity))
+            .cloned()
+        {
+            Either::Left(future::ready(Ok(results)))
+        } else {
+            let loader = self.call(token);
+            Either::Right(
+                loader.map(move |resp: Result<Response<SearchResult>, error::Error>| {
+                    let results = resp?.response;
+                    if let Some(cache) = cache {
+                        cache.insert(lat, lon, granularity, results.clone());
+                    }
+                    Ok(results)
+                }),
+            )
+        }
+    }
+
+    ///Returns whether `accuracy` and `max_results` are at their defaults, i.e. whether this lookup
+    ///can share results with other lookups in a `GeoCache`.
+    fn has_default_options(&self) -> bool {
+        self.accuracy.is_none()
+            && self.max_results.map_or(true, |count| count == 0 || count >= 20)
+    }
+}
+
+///A cache of reverse-geocode results by coordinate, for use with `GeocodeBuilder::call_cached`.
+///
+///Coordinates are rounded before they're saved or looked up, so nearby points share the same
+///results. By default, they're rounded to three decimal places, which is about 100 meters. It's up
+///to you to keep the cache around between calls; a new cache is empty.
+#[derive(Debug, Clone)]
+pub struct GeoCache {
+    precision: i32,
+    results: HashMap<(i64, i64, Option<PlaceType>), SearchResult>,
+}
+
+impl Default for GeoCache {
+    fn default() -> GeoCache {
+        GeoCache::new()
+    }
+}
+
+impl GeoCache {
+    ///Creates a new, empty `GeoCache` that rounds coordinates to three decimal places.
+    pub fn new() -> GeoCache {
+        GeoCache::with_precision(3)
+    }
+
+    ///Creates a new, empty `GeoCache` that rounds coordinates to the given number of decimal
+    ///places.
+    pub fn with_precision(decimals: u32) -> GeoCache {
+        GeoCache {
+            precision: decimals.min(15) as i32,
+            results: HashMap::new(),
+        }
+    }
+
+    ///Returns the saved results for the given coordinate and granularity, if any.
+    pub fn get(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        granularity: Option<PlaceType>,
+    ) -> Option<&SearchResult> {
+        self.results.get(&self.key(latitude, longitude, granularity))
+    }
+
+    ///Saves the given results for the given coordinate and granularity, replacing any results
+    ///saved for it previously.
+    pub fn insert(
+        &mut self,
+        latitude: f64,
+        longitude: f64,
+        granularity: Option<PlaceType>,
+        results: SearchResult,
+    ) {
+        let key = self.key(latitude, longitude, granularity);
+        self.results.insert(key, results);
+    }
+
+    ///Removes all the saved results.
+    pub fn clear(&mut self) {
+        self.results.clear();
+    }
+
+    fn key(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        granularity: Option<PlaceType>,
+    ) -> (i64, i64, Option<PlaceType>) {
+        let scale = 10f64.powi(self.precision);
+        let round = |val: f64| (val * scale).round() as i64;
+        (round(latitude), round(longitude), granularity)
+    }
 }
 
 enum PlaceQuery<'a> {
     LatLon(f64, f64),
     Query(&'a str),
     IPAddress(&'a str),
+    Similar(f64, f64, &'a str),
 }
 
 ///Represents a location search query before it is sent.
@@ -265,6 +507,9 @@ impl<'a> SearchBuilder<'a> {
 
     ///Sets the minimal specificity of what kind of results to return. For example, passing `City`
     ///to this will make the eventual result exclude neighborhoods and points.
+    ///
+    ///By default, no granularity is sent, and Twitter returns a spread of results of different
+    ///kinds, starting from the most specific. Use `clear_granularity` to go back to that.
     pub fn granularity(self, granularity: PlaceType) -> Self {
         SearchBuilder {
             granularity: Some(granularity),
@@ -272,6 +517,15 @@ impl<'a> SearchBuilder<'a> {
         }
     }
 
+    ///Clears a granularity set with `granularity`, so Twitter's default set of results is
+    ///returned.
+    pub fn clear_granularity(self) -> Self {
+        SearchBuilder {
+            granularity: None,
+            ..self
+        }
+    }
+
     ///Restricts the maximum number of results returned in this search. This is not a guarantee
     ///that the search will return this many results, but instead provides a hint as to how many
     ///"nearby" results to return.
@@ -330,6 +584,11 @@ impl<'a> SearchBuilder<'a> {
             PlaceQuery::IPAddress(text) => {
                 add_param(&mut params, "ip", text);
             }
+            PlaceQuery::Similar(lat, long, name) => {
+                add_param(&mut params, "lat", lat.to_string());
+                add_param(&mut params, "long", long.to_string());
+                add_param(&mut params, "name", name);
+            }
         }
 
         if let Some(ref acc) = self.accuracy {
@@ -354,7 +613,12 @@ impl<'a> SearchBuilder<'a> {
             }
         }
 
-        let req = auth::get(links::place::SEARCH, token, Some(&params));
+        let url = match self.query {
+            PlaceQuery::Similar(..) => links::place::SIMILAR_PLACES,
+            _ => links::place::SEARCH,
+        };
+
+        let req = auth::get(url, token, Some(&params));
 
         make_parsed_future(req)
     }
@@ -393,3 +657,69 @@ where
             serde_json::from_value::<Vec<(f64, f64)>>(inner_arr).map_err(|e| D::Error::custom(e))
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{reverse_geocode, Accuracy, GeoCache, PlaceType, SearchResult};
+
+    fn result(url: &str) -> SearchResult {
+        SearchResult {
+            url: url.to_string(),
+            results: vec![],
+            token: None,
+        }
+    }
+
+    #[test]
+    fn geo_cache_key_rounding() {
+        let cache = GeoCache::new();
+
+        assert_eq!(cache.key(37.78123, -122.40049, None), (37781, -122400, None));
+        assert_eq!(cache.key(37.7816, -122.4006, None), (37782, -122401, None));
+        assert_eq!(
+            cache.key(37.78123, -122.40049, Some(PlaceType::City)),
+            (37781, -122400, Some(PlaceType::City))
+        );
+    }
+
+    #[test]
+    fn geo_cache_precision() {
+        let coarse = GeoCache::with_precision(1);
+        assert_eq!(coarse.key(37.78123, -122.40049, None), (378, -1224, None));
+
+        let whole = GeoCache::with_precision(0);
+        assert_eq!(whole.key(37.78123, -122.40049, None), (38, -122, None));
+
+        //precision is capped at 15 decimal places
+        let fine = GeoCache::with_precision(40);
+        assert_eq!(fine.precision, 15);
+    }
+
+    #[test]
+    fn geo_cache_lookup() {
+        let mut cache = GeoCache::new();
+        cache.insert(37.78123, -122.40049, None, result("first"));
+
+        //nearby points round to the same key
+        assert_eq!(cache.get(37.7814, -122.4003, None).unwrap().url, "first");
+        assert!(cache.get(37.7826, -122.4003, None).is_none());
+        assert!(cache.get(37.7814, -122.4003, Some(PlaceType::City)).is_none());
+
+        cache.insert(37.7814, -122.4003, None, result("second"));
+        assert_eq!(cache.get(37.78123, -122.40049, None).unwrap().url, "second");
+
+        cache.clear();
+        assert!(cache.get(37.78123, -122.40049, None).is_none());
+    }
+
+    #[test]
+    fn geo_cache_default_options() {
+        assert!(reverse_geocode(37.78, -122.40).has_default_options());
+        assert!(reverse_geocode(37.78, -122.40).max_results(20).has_default_options());
+        assert!(reverse_geocode(37.78, -122.40).max_results(0).has_default_options());
+        assert!(!reverse_geocode(37.78, -122.40).max_results(1).has_default_options());
+        assert!(!reverse_geocode(37.78, -122.40)
+            .accuracy(Accuracy::Meters(50.0))
+            .has_default_options());
+    }
+}
@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A collection of the most commonly used types and functions, meant to be glob-imported.
+//!
+//! Most applications will use some combination of the types here, which are otherwise spread
+//! across several modules. Importing the prelude brings them all in at once:
+//!
+//! ```rust,no_run
+//! use egg_mode::prelude::*;
+//! use tokio::runtime::current_thread::block_on_all;
+//! # fn main() {
+//! # let token: Token = unimplemented!();
+//!
+//! let draft = DraftTweet::new("Hello from egg-mode!");
+//! let tweet: Response<Tweet> = block_on_all(draft.send(&token)).unwrap();
+//!
+//! let results = block_on_all(search("rustlang").call(&token)).unwrap();
+//! # }
+//! ```
+//!
+//! Note that this exports `error::Error` as `Error`, and `tweet::Timeline` as `Timeline`. If you
+//! also use the standard library's `Error` trait or `direct::Timeline`, import those by their full
+//! paths instead.
+
+pub use crate::auth::{KeyPair, Token};
+pub use crate::common::{FutureResponse, Response};
+pub use crate::cursor::{Cursor, CursorIter};
+pub use crate::direct::DirectMessage;
+pub use crate::error::Error;
+pub use crate::media::UploadBuilder;
+pub use crate::place::Place;
+pub use crate::search::{search, SearchQuery};
+pub use crate::tweet::{DraftTweet, Timeline, Tweet};
+pub use crate::user::{IdsExt, TwitterUser, UserID};
@@ -45,14 +45,30 @@
 //! these types use `Cow<'a, str>` internally, you can hand these types owned Strings to give them
 //! a `'static` lifetime, if necessary.
 //!
+//! Rather than writing out a query with operators by hand, you can also assemble one with
+//! `SearchQuery`, which handles quoting and operator syntax for you. A `SearchQuery` can be handed
+//! directly to `search`.
+//!
+//! Searches are always made in "extended" mode, so the `text` of each tweet in the results isn't
+//! truncated to 140 characters. Retweets still carry a truncated copy of the original's text; use
+//! `Tweet::original` to get to the retweeted tweet and its full text.
+//!
+//! If you only need to know how many tweets match a query, `count` pages through the results for
+//! you and only returns the number of tweets it found, up to a given limit.
+//!
 //! [search-doc]: https://dev.twitter.com/rest/public/search
 //! [search-place]: https://dev.twitter.com/rest/public/search-by-place
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::pin::Pin;
+use std::str::FromStr;
 
-use futures_core::Future;
+use futures_core::{Future, Poll};
+use futures_core::task::Context;
+use chrono::{self, Datelike};
+use futures_util::future::{self, Either};
 use futures_util::FutureExt;
 use serde::{Deserialize, Deserializer};
 
@@ -68,9 +84,91 @@ pub fn search<'a, S: Into<Cow<'a, str>>>(query: S) -> SearchBuilder<'a> {
         result_type: None,
         count: None,
         until: None,
+        since_date: None,
         geocode: None,
         since_id: None,
         max_id: None,
+        ext_media_availability: false,
+        include_entities: true,
+        dedupe: false,
+    }
+}
+
+///Count the tweets that match the given query, up to `max`.
+///
+///This loads pages of recent search results for the query, and stops as soon as it runs out of
+///results or finds `max` tweets, so only as many pages are loaded as needed. Each page loads up to
+///100 tweets, and each page counts against the rate limit for searches like a regular call to
+///`SearchBuilder::call`.
+///
+///Note that the standard search API only searches tweets from the last seven days, so this can
+///only count tweets from that period.
+pub fn count<'a, S: Into<Cow<'a, str>>>(
+    query: S,
+    max: usize,
+    token: &auth::Token,
+) -> CountFuture {
+    let mut params = HashMap::new();
+    add_param(&mut params, "q", query.into().into_owned());
+    add_param(&mut params, "result_type", ResultType::Recent.to_string());
+    add_param(&mut params, "count", "100");
+    add_param(&mut params, "tweet_mode", "extended");
+
+    let loader = if max > 0 {
+        let req = auth::get(links::statuses::SEARCH, token, Some(&params));
+        Some(make_parsed_future(req))
+    } else {
+        None
+    };
+
+    CountFuture {
+        token: token.clone(),
+        params,
+        max,
+        total: 0,
+        loader,
+    }
+}
+
+///A `Future` that pages through search results and counts them, as returned by `count`.
+#[must_use = "futures do nothing unless polled"]
+pub struct CountFuture {
+    token: auth::Token,
+    params: ParamList<'static>,
+    max: usize,
+    total: usize,
+    loader: Option<FutureResponse<SearchResult<'static>>>,
+}
+
+impl Future for CountFuture {
+    type Output = Result<usize, error::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            let page = match this.loader.as_mut() {
+                Some(loader) => match loader.poll_unpin(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(page) => page,
+                },
+                None => return Poll::Ready(Ok(this.total.min(this.max))),
+            };
+            this.loader = None;
+
+            let page = page?;
+            this.total += page.statuses.len();
+
+            //Twitter leaves out `next_results` on the last page, so don't spend another call on
+            //an empty page after it
+            if this.total < this.max && page.next_results.is_some() {
+                if let Some(min_id) = page.statuses.iter().map(|t| t.id).min() {
+                    add_param(&mut this.params, "max_id", min_id.saturating_sub(1).to_string());
+                    let req = auth::get(links::statuses::SEARCH, &this.token, Some(&this.params));
+                    this.loader = Some(make_parsed_future(req));
+                }
+            }
+        }
     }
 }
 
@@ -96,6 +194,263 @@ impl fmt::Display for ResultType {
     }
 }
 
+///Parses the same strings given by the `Display` impl, ignoring case.
+impl FromStr for ResultType {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<ResultType, error::Error> {
+        match s.to_lowercase().as_str() {
+            "recent" => Ok(ResultType::Recent),
+            "popular" => Ok(ResultType::Popular),
+            "mixed" => Ok(ResultType::Mixed),
+            _ => Err(error::Error::BadParameter(
+                "expected one of \"recent\", \"popular\", or \"mixed\"",
+                s.to_string(),
+            )),
+        }
+    }
+}
+
+///The language codes that Twitter can detect on tweets.
+const LANGUAGES: &[&str] = &[
+    "am", "ar", "bg", "bn", "bo", "ca", "ckb", "cs", "cy", "da", "de", "dv", "el", "en", "es",
+    "et", "eu", "fa", "fi", "fil", "fr", "ga", "gl", "gu", "he", "hi", "hr", "ht", "hu", "hy",
+    "id", "in", "is", "it", "iw", "ja", "ka", "km", "kn", "ko", "lo", "lt", "lv", "ml", "mr",
+    "ms", "my", "ne", "nl", "no", "or", "pa", "pl", "ps", "pt", "ro", "ru", "sd", "si", "sk",
+    "sl", "sr", "sv", "ta", "te", "th", "tl", "tr", "ug", "uk", "ur", "vi", "zh", "zh-cn",
+    "zh-tw",
+];
+
+///A language code that Twitter can use to filter search results.
+///
+///Twitter uses a subset of BCP 47 language codes (mostly two-letter ISO 639-1 codes) to mark what
+///language it has detected a tweet to be written in. Searching with a code that Twitter doesn't
+///know won't return an error, just an empty set of results, so `Language` checks the code against
+///the ones Twitter is known to use before it's sent.
+///
+///To get a `Language`, parse it from a string:
+///
+///```rust
+///use egg_mode::search::Language;
+///
+///let lang: Language = "en".parse().unwrap();
+///assert_eq!(lang.as_str(), "en");
+///
+///assert!("klingon".parse::<Language>().is_err());
+///```
+///
+///A `Language` can be handed directly to `SearchBuilder::lang`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Language(&'static str);
+
+impl Language {
+    ///Returns the language code as a string.
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+///Parses a language code, ignoring case.
+impl FromStr for Language {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Language, error::Error> {
+        let lower = s.to_lowercase();
+        LANGUAGES
+            .iter()
+            .find(|&&lang| lang == lower)
+            .map(|&lang| Language(lang))
+            .ok_or_else(|| {
+                error::Error::BadParameter("unknown language code", s.to_string())
+            })
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'a> From<Language> for Cow<'a, str> {
+    fn from(lang: Language) -> Cow<'a, str> {
+        Cow::Borrowed(lang.0)
+    }
+}
+
+///A builder for search queries that use Twitter's search operators.
+///
+///Each method adds one condition to the query, and all the conditions must match for a tweet to
+///be returned, except for the terms given to `any_of`, only one of which needs to match. Terms are
+///quoted as necessary so that spaces or operator characters in them are searched for literally.
+///
+///```rust
+///use egg_mode::search::SearchQuery;
+///
+///let query = SearchQuery::new()
+///    .from("rustlang")
+///    .phrase("release notes")
+///    .any_of(&["beta", "nightly"])
+///    .exclude("survey")
+///    .has_media();
+///
+///assert_eq!(query.to_string(),
+///           "from:rustlang \"release notes\" (beta OR nightly) -survey filter:media");
+///```
+///
+///A `SearchQuery` can be handed directly to `search`, or turned into a string with `to_string`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    terms: Vec<String>,
+}
+
+impl SearchQuery {
+    ///Creates a new, empty `SearchQuery`.
+    pub fn new() -> SearchQuery {
+        SearchQuery::default()
+    }
+
+    ///Searches for tweets containing the given term. If the term contains spaces or characters
+    ///that Twitter would treat as an operator, it's searched for as an exact phrase.
+    pub fn term<S: AsRef<str>>(self, term: S) -> Self {
+        self.push(quote_term(term.as_ref()))
+    }
+
+    ///Searches for tweets containing the given exact phrase.
+    pub fn phrase<S: AsRef<str>>(self, text: S) -> Self {
+        self.push(quote_phrase(text.as_ref()))
+    }
+
+    ///Searches for tweets posted by the given user. A leading `@` is optional.
+    pub fn from<S: AsRef<str>>(self, user: S) -> Self {
+        self.push_user("from:", user.as_ref())
+    }
+
+    ///Searches for tweets replying to the given user. A leading `@` is optional.
+    pub fn to<S: AsRef<str>>(self, user: S) -> Self {
+        self.push_user("to:", user.as_ref())
+    }
+
+    ///Searches for tweets mentioning the given user. A leading `@` is optional.
+    pub fn mentioning<S: AsRef<str>>(self, user: S) -> Self {
+        self.push_user("@", user.as_ref())
+    }
+
+    ///Searches for tweets containing the given hashtag. A leading `#` is optional.
+    pub fn hashtag<S: AsRef<str>>(self, tag: S) -> Self {
+        let tag = tag.as_ref().trim_start_matches('#');
+        let tag = tag
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '_')
+            .collect::<String>();
+        if tag.is_empty() {
+            self
+        } else {
+            self.push(format!("#{}", tag))
+        }
+    }
+
+    ///Only returns tweets with attached images or videos.
+    pub fn has_media(self) -> Self {
+        self.push("filter:media".to_string())
+    }
+
+    ///Excludes tweets containing the given term. Like `term`, the term is searched for as an
+    ///exact phrase if necessary.
+    pub fn exclude<S: AsRef<str>>(self, term: S) -> Self {
+        let term = quote_term(term.as_ref());
+        self.push(format!("-{}", term))
+    }
+
+    ///Searches for tweets containing at least one of the given terms. Like `term`, each term is
+    ///searched for as an exact phrase if necessary.
+    pub fn any_of<I, S>(self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let terms = terms
+            .into_iter()
+            .map(|t| quote_term(t.as_ref()))
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>();
+
+        match terms.len() {
+            0 => self,
+            1 => self.push(terms.into_iter().next().unwrap()),
+            _ => self.push(format!("({})", terms.join(" OR "))),
+        }
+    }
+
+    ///Adds the given operator and screen name to the query, skipping it if the screen name is
+    ///empty.
+    fn push_user(self, operator: &str, user: &str) -> Self {
+        let user = screen_name(user);
+        if user.is_empty() {
+            self
+        } else {
+            self.push(format!("{}{}", operator, user))
+        }
+    }
+
+    ///Adds the given condition to the query, skipping it if it's empty.
+    fn push(mut self, term: String) -> Self {
+        if !term.is_empty() {
+            self.terms.push(term);
+        }
+        self
+    }
+}
+
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.terms.join(" "))
+    }
+}
+
+impl<'a> From<SearchQuery> for Cow<'a, str> {
+    fn from(query: SearchQuery) -> Cow<'a, str> {
+        Cow::Owned(query.to_string())
+    }
+}
+
+///Quotes the given term if Twitter would otherwise read it as more than a single plain word.
+fn quote_term(term: &str) -> String {
+    let term = term.trim();
+    let needs_quotes = term == "OR"
+        || term.starts_with(|c| c == '-' || c == '@' || c == '#' || c == '$')
+        || term
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '(' || c == ')' || c == ':');
+
+    if needs_quotes {
+        quote_phrase(term)
+    } else {
+        term.to_string()
+    }
+}
+
+///Wraps the given text in quotes. Twitter has no way to escape a quote inside a phrase, so any
+///quotes in the text are replaced with spaces.
+fn quote_phrase(text: &str) -> String {
+    let text = text.replace('"', " ");
+    let text = text.trim();
+
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("\"{}\"", text)
+    }
+}
+
+///Strips a leading `@` and any characters that can't appear in a screen name.
+fn screen_name(user: &str) -> String {
+    user.trim_start_matches('@')
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect()
+}
+
 ///Represents a radius around a given location to return search results for.
 pub enum Distance {
     ///A radius given in miles.
@@ -113,14 +468,23 @@ pub struct SearchBuilder<'a> {
     result_type: Option<ResultType>,
     count: Option<u32>,
     until: Option<(u32, u32, u32)>,
+    since_date: Option<chrono::NaiveDate>,
     geocode: Option<(f32, f32, Distance)>,
     since_id: Option<u64>,
     max_id: Option<u64>,
+    ext_media_availability: bool,
+    include_entities: bool,
+    dedupe: bool,
 }
 
 impl<'a> SearchBuilder<'a> {
     ///Restrict search results to those that have been machine-parsed as the given two-letter
     ///language code.
+    ///
+    ///This accepts a plain string without checking it. To make sure the code is one that Twitter
+    ///recognizes, parse it into a [`Language`] first and hand that in instead.
+    ///
+    ///[`Language`]: struct.Language.html
     pub fn lang<S: Into<Cow<'a, str>>>(self, lang: S) -> Self {
         SearchBuilder {
             lang: Some(lang.into()),
@@ -145,8 +509,8 @@ impl<'a> SearchBuilder<'a> {
     }
 
     ///Returns tweets created before the given date. Keep in mind that search is limited to the
-    ///last 7 days of results, so giving a date here that's older than a week will return no
-    ///results.
+    ///last 7 days of results, so if the date given here is older than a week, `call` will return a
+    ///`SearchWindow` error instead of an empty set of results.
     pub fn until(self, year: u32, month: u32, day: u32) -> Self {
         SearchBuilder {
             until: Some((year, month, day)),
@@ -154,6 +518,29 @@ impl<'a> SearchBuilder<'a> {
         }
     }
 
+    ///Returns tweets created before the given date. This is the same as `until`, but takes a
+    ///`NaiveDate`.
+    ///
+    ///Twitter's search only covers the last 7 days of tweets, so if the given date is older than
+    ///that, `call` will return a `SearchWindow` error instead of an empty set of results.
+    pub fn until_date(self, date: chrono::NaiveDate) -> Self {
+        self.until(date.year() as u32, date.month(), date.day())
+    }
+
+    ///Returns tweets created on or after the given date, by adding a `since:` operator to the
+    ///query.
+    ///
+    ///Twitter's search only covers the last 7 days of tweets, so if the given date is older than
+    ///that (or is in the future), `call` will return a `SearchWindow` error instead of an empty
+    ///set of results. `call` will also return a `BadParameter` error if this is set to a date
+    ///after the one given to `until`.
+    pub fn since_date(self, date: chrono::NaiveDate) -> Self {
+        SearchBuilder {
+            since_date: Some(date),
+            ..self
+        }
+    }
+
     ///Restricts results to users located within the given radius of the given coordinate. This is
     ///preferably populated from location-tagged tweets, but can be filled in from the user's
     ///profile as a fallback.
@@ -182,14 +569,132 @@ impl<'a> SearchBuilder<'a> {
         }
     }
 
+    ///Restricts results to those with higher IDs than the given tweet ID. This is the same as
+    ///`since_tweet`.
+    ///
+    ///If this is set along with `max_id`, it must be lower than `max_id`, or `call` will return a
+    ///`BadParameter` error.
+    pub fn since_id(self, since_id: u64) -> Self {
+        self.since_tweet(since_id)
+    }
+
+    ///Restricts results to those with IDs no higher than the given tweet ID. This is the same as
+    ///`max_tweet`.
+    pub fn max_id(self, max_id: u64) -> Self {
+        self.max_tweet(max_id)
+    }
+
+    ///Ask Twitter to say whether the media attached to each tweet is available to view.
+    ///
+    ///If this is set, the `ext_media_availability` field of each `MediaEntity` in the results will
+    ///be filled in. The default is `false`.
+    pub fn include_ext_media_availability(self, include: bool) -> Self {
+        SearchBuilder {
+            ext_media_availability: include,
+            ..self
+        }
+    }
+
+    ///Set whether Twitter should include the entities of each tweet in the results.
+    ///
+    ///If this is set to `false`, Twitter leaves out the hashtags, links, mentions, and media of
+    ///each tweet, and the `entities` of each returned tweet will be empty. This makes each page
+    ///smaller and quicker to parse, for searches that only need the text and IDs of the tweets.
+    ///The default is `true`.
+    pub fn include_entities(self, include: bool) -> Self {
+        SearchBuilder {
+            include_entities: include,
+            ..self
+        }
+    }
+
+    ///Checks that the date and ID bounds set on this search can return results.
+    fn check_bounds(&self) -> Result<(), error::Error> {
+        self.check_bounds_on(chrono::Utc::today().naive_utc())
+    }
+
+    ///Checks the date and ID bounds set on this search as if it were run on the given (UTC) date.
+    fn check_bounds_on(&self, today: chrono::NaiveDate) -> Result<(), error::Error> {
+        let oldest = today - chrono::Duration::days(7);
+
+        let until = match self.until {
+            Some((year, month, day)) => {
+                let date = chrono::NaiveDate::from_ymd_opt(year as i32, month, day).ok_or_else(
+                    || {
+                        error::Error::BadParameter(
+                            "a valid date for `until`",
+                            format!("{}-{}-{}", year, month, day),
+                        )
+                    },
+                )?;
+                if date < oldest {
+                    return Err(error::Error::SearchWindow(date));
+                }
+                Some(date)
+            }
+            None => None,
+        };
+
+        if let Some(since) = self.since_date {
+            if since < oldest || since > today {
+                return Err(error::Error::SearchWindow(since));
+            }
+            if let Some(until) = until {
+                if since > until {
+                    return Err(error::Error::BadParameter(
+                        "a `since_date` no later than `until`",
+                        since.to_string(),
+                    ));
+                }
+            }
+        }
+
+        if let (Some(since_id), Some(max_id)) = (self.since_id, self.max_id) {
+            if since_id >= max_id {
+                return Err(error::Error::BadParameter(
+                    "a `since_id` lower than `max_id`",
+                    since_id.to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    ///Skip tweets that were already returned in an earlier page of this search.
+    ///
+    ///When paging through results with `older` and `newer`, new tweets arriving in between calls
+    ///can shift the pages so that some tweets are returned twice. If this is set, the
+    ///`SearchResult` remembers the IDs of the tweets it's returned, and each following page only
+    ///contains tweets that weren't in an earlier page. This means a page can come back with fewer
+    ///tweets than were requested, or none at all. The default is `false`, which returns the pages
+    ///as Twitter sent them.
+    pub fn dedupe(self, dedupe: bool) -> Self {
+        SearchBuilder { dedupe, ..self }
+    }
+
     ///Finalize the search terms and return the first page of responses.
+    ///
+    ///If the date or ID bounds given to this search can't return any results, e.g. because a date
+    ///is outside the last 7 days that Twitter's search covers, the returned future yields an error
+    ///without calling Twitter.
     pub fn call(
         self,
         token: &auth::Token,
     ) -> impl Future<Output = Result<Response<SearchResult<'a>>, error::Error>> {
+        if let Err(e) = self.check_bounds() {
+            return Either::Left(future::ready(Err(e)));
+        }
+
         let mut params = HashMap::new();
+        add_param(&mut params, "tweet_mode", "extended");
 
-        add_param(&mut params, "q", self.query);
+        if let Some(since) = self.since_date {
+            let query = format!("{} since:{}", self.query, since.format("%Y-%m-%d"));
+            add_param(&mut params, "q", query);
+        } else {
+            add_param(&mut params, "q", self.query);
+        }
 
         if let Some(lang) = self.lang {
             add_param(&mut params, "lang", lang);
@@ -230,13 +735,26 @@ impl<'a> SearchBuilder<'a> {
             add_param(&mut params, "max_id", max_id.to_string());
         }
 
+        if self.ext_media_availability {
+            add_param(&mut params, "include_ext_media_availability", "true");
+        }
+
+        if !self.include_entities {
+            add_param(&mut params, "include_entities", "false");
+        }
+
         let req = auth::get(links::statuses::SEARCH, token, Some(&params));
 
-        make_parsed_future(req).map(move |resp: Result<Response<SearchResult>, error::Error>| {
-            let mut local_resp = resp.unwrap();
-            local_resp.response.params = Some(params);
-            Ok(local_resp)
-        })
+        let seen = if self.dedupe { Some(HashSet::new()) } else { None };
+
+        Either::Right(make_parsed_future(req).map(
+            move |resp: Result<Response<SearchResult>, error::Error>| {
+                let mut local_resp = resp?;
+                local_resp.response.params = Some(params);
+                local_resp.response.skip_seen(seen);
+                Ok(local_resp)
+            },
+        ))
     }
 }
 
@@ -266,12 +784,20 @@ impl<'de> Deserialize<'de> for SearchResult<'static> {
         D: Deserializer<'de>,
     {
         let raw = RawSearch::deserialize(deser)?;
+        let min = raw.statuses.iter().map(|t| t.id).min();
+        let max = raw.statuses.iter().map(|t| t.id).max();
+        let page_range = min.and_then(|min| max.map(|max| (min, max)));
+
         Ok(SearchResult {
             statuses: raw.statuses,
             query: raw.search_metadata.query.into(),
             max_id: raw.search_metadata.max_id,
             since_id: raw.search_metadata.since_id,
+            next_results: raw.search_metadata.next_results.map(String::from),
+            refresh_url: raw.search_metadata.refresh_url.map(String::from),
             params: None,
+            seen: None,
+            page_range,
         })
     }
 }
@@ -288,10 +814,39 @@ pub struct SearchResult<'a> {
     pub max_id: u64,
     ///First tweet id in this page of results. This id can be used in `SearchBuilder::since_tweet`
     pub since_id: u64,
+    ///The query string Twitter gave to load the next (older) page of results, or `None` if there
+    ///are no more results. This can be turned back into parameters with `egg_mode::parse_query`.
+    pub next_results: Option<String>,
+    ///The query string Twitter gave to check for newer results for the same search, or `None` if
+    ///this page had no results.
+    pub refresh_url: Option<String>,
     params: Option<ParamList<'a>>,
+    ///If the search was set to `dedupe`, the IDs of every tweet returned so far.
+    seen: Option<HashSet<u64>>,
+    ///The `(min, max)` tweet IDs Twitter returned for this page, before any tweets were removed
+    ///from `statuses`, or `None` if the page was empty.
+    page_range: Option<(u64, u64)>,
 }
 
 impl<'a> SearchResult<'a> {
+    ///Removes the tweets in this page of results that Twitter flagged as possibly sensitive.
+    ///
+    ///Tweets are only flagged when they contain a link or media, so tweets with no
+    ///`possibly_sensitive` flag at all are kept. `older` and `newer` still page from the tweets
+    ///Twitter returned, so removing tweets here doesn't change which pages they load.
+    pub fn retain_safe(&mut self) {
+        self.statuses.retain(|t| t.possibly_sensitive != Some(true));
+    }
+
+    ///If given a set of already-seen tweet IDs, removes those tweets from this page and saves the
+    ///set (with this page's tweets added) for the next page.
+    fn skip_seen(&mut self, seen: Option<HashSet<u64>>) {
+        if let Some(mut seen) = seen {
+            self.statuses.retain(|t| seen.insert(t.id));
+            self.seen = Some(seen);
+        }
+    }
+
     ///Load the next page of search results for the same query.
     pub fn older(
         &self,
@@ -300,17 +855,19 @@ impl<'a> SearchResult<'a> {
         let mut params = self.params.as_ref().cloned().unwrap_or_default();
         params.remove("since_id");
 
-        if let Some(min_id) = self.statuses.iter().map(|t| t.id).min() {
-            add_param(&mut params, "max_id", (min_id - 1).to_string());
+        if let Some((min_id, _)) = self.page_range {
+            add_param(&mut params, "max_id", min_id.saturating_sub(1).to_string());
         } else {
             params.remove("max_id");
         }
 
         let req = auth::get(links::statuses::SEARCH, token, Some(&params));
+        let seen = self.seen.clone();
 
         make_parsed_future(req).map(move |resp: Result<Response<SearchResult>, error::Error>| {
-            let mut local_resp = resp.unwrap();
+            let mut local_resp = resp?;
             local_resp.response.params = Some(params);
+            local_resp.response.skip_seen(seen);
             Ok(local_resp)
         })
     }
@@ -323,18 +880,139 @@ impl<'a> SearchResult<'a> {
         let mut params = self.params.as_ref().cloned().unwrap_or_default();
         params.remove("max_id");
 
-        if let Some(max_id) = self.statuses.iter().map(|t| t.id).max() {
+        if let Some((_, max_id)) = self.page_range {
             add_param(&mut params, "since_id", max_id.to_string());
         } else {
             params.remove("since_id");
         }
 
         let req = auth::get(links::statuses::SEARCH, token, Some(&params));
+        let seen = self.seen.clone();
 
         make_parsed_future(req).map(move |resp: Result<Response<SearchResult>, error::Error>| {
-            let mut local_resp = resp.unwrap();
+            let mut local_resp = resp?;
             local_resp.response.params = Some(params);
+            local_resp.response.skip_seen(seen);
             Ok(local_resp)
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{quote_phrase, quote_term, screen_name, search, SearchQuery};
+    use crate::error::Error;
+
+    #[test]
+    fn quote_operator_terms() {
+        assert_eq!(quote_term("rust"), "rust");
+        assert_eq!(quote_term("  rust "), "rust");
+        assert_eq!(quote_term("OR"), "\"OR\"");
+        assert_eq!(quote_term("or"), "or");
+
+        assert_eq!(quote_term("-rust"), "\"-rust\"");
+        assert_eq!(quote_term("@rustlang"), "\"@rustlang\"");
+        assert_eq!(quote_term("#rustlang"), "\"#rustlang\"");
+        assert_eq!(quote_term("$TSLA"), "\"$TSLA\"");
+        assert_eq!(quote_term("half-baked"), "half-baked");
+    }
+
+    #[test]
+    fn quote_embedded_characters() {
+        assert_eq!(quote_term("say \"hi\""), "\"say  hi\"");
+        assert_eq!(quote_term("from:rustlang"), "\"from:rustlang\"");
+        assert_eq!(quote_term("(rust)"), "\"(rust)\"");
+        assert_eq!(quote_phrase("\"quoted\""), "\"quoted\"");
+    }
+
+    #[test]
+    fn quote_empty_terms() {
+        assert_eq!(quote_term(""), "");
+        assert_eq!(quote_term("   "), "");
+        assert_eq!(quote_phrase(" \" "), "");
+        assert_eq!(SearchQuery::new().term("").phrase("  ").exclude(" ").to_string(), "");
+    }
+
+    #[test]
+    fn query_screen_names() {
+        assert_eq!(screen_name("@rustlang"), "rustlang");
+        assert_eq!(screen_name("rust lang!"), "rustlang");
+        assert_eq!(SearchQuery::new().from("@").to("").to_string(), "");
+        assert_eq!(SearchQuery::new().mentioning("@rustlang").to_string(), "@rustlang");
+    }
+
+    #[test]
+    fn query_any_of() {
+        let none: &[&str] = &[];
+        assert_eq!(SearchQuery::new().any_of(none).to_string(), "");
+        assert_eq!(SearchQuery::new().any_of(&["", " "]).to_string(), "");
+        assert_eq!(SearchQuery::new().any_of(&["beta"]).to_string(), "beta");
+        assert_eq!(
+            SearchQuery::new().any_of(&["beta", "", "release notes", "OR"]).to_string(),
+            "(beta OR \"release notes\" OR \"OR\")"
+        );
+    }
+
+    #[test]
+    fn query_hashtags() {
+        assert_eq!(SearchQuery::new().hashtag("rustlang").to_string(), "#rustlang");
+        assert_eq!(SearchQuery::new().hashtag("##rust-lang!").to_string(), "#rustlang");
+        assert_eq!(SearchQuery::new().hashtag("rust_2018").to_string(), "#rust_2018");
+        assert_eq!(SearchQuery::new().hashtag("#!?").to_string(), "");
+    }
+
+    #[test]
+    fn search_bounds() {
+        let today = NaiveDate::from_ymd(2019, 10, 15);
+        let check = |builder: super::SearchBuilder| builder.check_bounds_on(today);
+
+        assert!(check(search("rust")).is_ok());
+        assert!(check(search("rust").until(2019, 10, 8)).is_ok());
+        assert!(check(search("rust").since_date(NaiveDate::from_ymd(2019, 10, 8))).is_ok());
+        assert!(check(search("rust").since_date(today).until(2019, 10, 16)).is_ok());
+
+        match check(search("rust").until(2019, 10, 7)) {
+            Err(Error::SearchWindow(date)) => assert_eq!(date, NaiveDate::from_ymd(2019, 10, 7)),
+            other => panic!("expected a SearchWindow error, got {:?}", other),
+        }
+
+        let old = NaiveDate::from_ymd(2019, 10, 1);
+        match check(search("rust").since_date(old)) {
+            Err(Error::SearchWindow(date)) => assert_eq!(date, old),
+            other => panic!("expected a SearchWindow error, got {:?}", other),
+        }
+
+        let future = NaiveDate::from_ymd(2019, 10, 16);
+        match check(search("rust").since_date(future)) {
+            Err(Error::SearchWindow(date)) => assert_eq!(date, future),
+            other => panic!("expected a SearchWindow error, got {:?}", other),
+        }
+
+        let since = NaiveDate::from_ymd(2019, 10, 12);
+        match check(search("rust").since_date(since).until(2019, 10, 11)) {
+            Err(Error::BadParameter(_, val)) => assert_eq!(val, "2019-10-12"),
+            other => panic!("expected a BadParameter error, got {:?}", other),
+        }
+
+        match check(search("rust").until(2019, 2, 30)) {
+            Err(Error::BadParameter(_, val)) => assert_eq!(val, "2019-2-30"),
+            other => panic!("expected a BadParameter error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn search_id_bounds() {
+        let today = NaiveDate::from_ymd(2019, 10, 15);
+
+        assert!(search("rust").since_id(10).max_id(11).check_bounds_on(today).is_ok());
+
+        for &(since_id, max_id) in &[(11, 11), (12, 11)] {
+            match search("rust").since_id(since_id).max_id(max_id).check_bounds_on(today) {
+                Err(Error::BadParameter(_, val)) => assert_eq!(val, since_id.to_string()),
+                other => panic!("expected a BadParameter error, got {:?}", other),
+            }
+        }
+    }
+}
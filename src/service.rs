@@ -146,6 +146,8 @@ pub struct Configuration {
     pub dm_text_character_limit: i32,
     ///The maximum dimensions for each photo size variant.
     pub photo_sizes: entities::MediaSizes,
+    ///The maximum size of an uploaded photo, in bytes.
+    pub photo_size_limit: u64,
     ///The maximum length for a t.co URL when given a URL with protocol `http`.
     pub short_url_length: i32,
     ///The maximum length for a t.co URL when given a URL with protocol `https`.
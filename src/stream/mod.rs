@@ -39,11 +39,19 @@
 //! such that after (say) 1 minute of inactivity, the client bounces the connection. This will protect
 //! against network stalls
 //! * Twitter will rate-limit reconnect attempts. So attempt conenctions with a linear or exponential
-//! backoff strategy
+//! backoff strategy. If the stream returns `Error::EnhanceYourCalm` (HTTP 420), you've reconnected
+//! too often: wait at least a minute, and double the wait for each consecutive 420. `Backoff`
+//! gives these schedules as an iterator of delays
 //! * In the case of an unreliable connection (e.g. mobile network), fall back to the polling API
 //!
 //! The [official guide](https://developer.twitter.com/en/docs/tweets/filter-realtime/guides/connecting) has more information.
-use std::{self, io};
+//!
+//! ### User streams
+//!
+//! egg-mode doesn't support user streams, which Twitter retired in 2018, so the user-stream
+//! `with` and `replies` parameters aren't available here. The public streams above don't accept
+//! them. To follow the tweets of a set of accounts, give their IDs to `StreamBuilder::follow`;
+//! note that this includes replies *to* those accounts from anyone, similar to `replies=all`.
 use std::collections::HashMap;
 use std::pin::Pin;
 use std::str::FromStr;
@@ -199,7 +207,7 @@ impl FromStr for StreamMessage {
         if input.is_empty() {
             Ok(StreamMessage::Ping)
         } else {
-            Ok(serde_json::from_str(input)?)
+            parse_json(input)
         }
     }
 }
@@ -208,20 +216,60 @@ impl FromStr for StreamMessage {
 #[must_use = "Streams are lazy and do nothing unless polled"]
 pub struct TwitterStream {
     buf: Vec<u8>,
-    request: Option<Request<Body>>,
+    request: Option<Result<Request<Body>, error::Error>>,
     response: Option<ResponseFuture>,
     body: Option<Body>,
+    delimited: bool,
 }
 
 impl TwitterStream {
-    fn new(request: Request<Body>) -> TwitterStream {
+    fn new(request: Result<Request<Body>, error::Error>) -> TwitterStream {
         TwitterStream {
             buf: vec![],
             request: Some(request),
             response: None,
             body: None,
+            delimited: false,
         }
     }
+
+    /// Pulls the next complete message out of the buffer, if one has been fully received.
+    fn next_message(&mut self) -> Option<Result<StreamMessage, error::Error>> {
+        let pos = self.buf.windows(2).position(|w| w == b"\r\n")?;
+
+        let (start, end) = if self.delimited {
+            let prefix = String::from_utf8_lossy(&self.buf[..pos]).trim().to_string();
+            if prefix.is_empty() {
+                //keep-alive newlines are sent without a length prefix
+                (0, pos + 2)
+            } else {
+                match prefix.parse::<usize>() {
+                    Ok(len) => (pos + 2, pos + 2 + len),
+                    Err(_) => {
+                        self.buf.drain(..pos + 2);
+                        return Some(Err(error::Error::InvalidResponse(
+                            "invalid length prefix in stream",
+                            Some(prefix),
+                        )));
+                    }
+                }
+            }
+        } else {
+            (0, pos + 2)
+        };
+
+        if self.buf.len() < end {
+            return None;
+        }
+
+        let resp = match std::str::from_utf8(&self.buf[start..end]) {
+            Ok(msg_str) => StreamMessage::from_str(msg_str),
+            Err(err) => Err(err.into()),
+        };
+
+        self.buf.drain(..end);
+        Some(resp)
+    }
 }
 
 impl Stream for TwitterStream {
@@ -230,8 +278,10 @@ impl Stream for TwitterStream {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let mut_self = self.get_mut();
         if let Some(req) = mut_self.request.take() {
-            // TODO handle error
-            mut_self.response = Some(get_response(req).unwrap());
+            match req.and_then(get_response) {
+                Ok(resp) => mut_self.response = Some(resp),
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
         }
 
         if let Some(mut resp) = mut_self.response.take() {
@@ -243,9 +293,15 @@ impl Stream for TwitterStream {
                 }
                 Poll::Ready(Ok(resp)) => {
                     let status = resp.status();
-                    if !status.is_success() {
+                    if status.as_u16() == ENHANCE_YOUR_CALM {
+                        return Poll::Ready(Some(Err(error::Error::EnhanceYourCalm)));
+                    } else if !status.is_success() {
                         //TODO: should i try to pull the response regardless?
-                        return Poll::Ready(Some(Err(error::Error::BadStatus(status))));
+                        return Poll::Ready(Some(Err(error::Error::BadStatus {
+                            status,
+                            retry_after: retry_after(resp.headers()),
+                            meta: response_meta(resp.headers()),
+                        })));
                     }
 
                     mut_self.body = Some(resp.into_body());
@@ -255,6 +311,13 @@ impl Stream for TwitterStream {
 
         if let Some(mut body) = mut_self.body.take() {
             loop {
+                //a single chunk can contain several messages, so hand out any complete message
+                //that's already buffered before waiting on the connection again
+                if let Some(resp) = mut_self.next_message() {
+                    mut_self.body = Some(body);
+                    return Poll::Ready(Some(resp));
+                }
+
                 match body.poll_next_unpin(cx) {
                     Poll::Ready(Some(Err(e))) => {
                         mut_self.body = Some(body);
@@ -270,24 +333,6 @@ impl Stream for TwitterStream {
                     }
                     Poll::Ready(Some(Ok(chunk))) => {
                         mut_self.buf.extend(&*chunk);
-
-                        if let Some(pos) = mut_self.buf.windows(2).position(|w| w == b"\r\n") {
-                            mut_self.body = Some(body);
-                            let pos = pos + 2;
-                            let resp =
-                                if let Ok(msg_str) = std::str::from_utf8(&mut_self.buf[..pos]) {
-                                    StreamMessage::from_str(msg_str)
-                                } else {
-                                    Err(io::Error::new(
-                                        io::ErrorKind::InvalidData,
-                                        "stream did not contain valid UTF-8",
-                                    )
-                                    .into())
-                                };
-
-                            mut_self.buf.drain(..pos);
-                            return Poll::Ready(Some(Ok(resp?)));
-                        }
                     }
                 }
             }
@@ -302,7 +347,11 @@ impl Stream for TwitterStream {
 /// According to Twitter's documentation, "When displaying a stream of Tweets to end users
 /// (dashboards or live feeds at a presentation or conference, for example) it is suggested that
 /// you set this value to medium."
-#[derive(Copy, Clone, Debug, Deserialize)]
+///
+/// `FilterLevel`s are ordered from the least filtering to the most, so a tweet whose
+/// `filter_level` is at least the level given to `StreamBuilder::filter_level` will be included in
+/// that stream.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
 pub enum FilterLevel {
     /// No filtering.
     #[serde(rename = "none")]
@@ -345,6 +394,7 @@ pub struct StreamBuilder {
     language: Vec<String>,
     locations: Vec<BoundingBox>,
     filter_level: Option<FilterLevel>,
+    delimited: bool,
 }
 
 impl StreamBuilder {
@@ -356,6 +406,7 @@ impl StreamBuilder {
             language: Vec::new(),
             locations: Vec::new(),
             filter_level: None,
+            delimited: false,
         }
     }
 
@@ -432,6 +483,16 @@ impl StreamBuilder {
         }
     }
 
+    /// Asks Twitter to prefix each message with its length in bytes, and uses that length to
+    /// split messages apart.
+    ///
+    /// By default, messages are split at each newline. Length-delimited framing doesn't rely on
+    /// where newlines fall in the stream, which makes it more robust for high-volume streams.
+    /// Either way, the resulting `TwitterStream` yields the same messages.
+    pub fn delimited(self, delimited: bool) -> StreamBuilder {
+        StreamBuilder { delimited, ..self }
+    }
+
     /// Finalizes the stream parameters and returns the resulting `TwitterStream`.
     pub fn start(self, token: &Token) -> TwitterStream {
         // Re connection failure, arguably this library should check that either 'track' or
@@ -445,6 +506,10 @@ impl StreamBuilder {
             add_param(&mut params, "filter_level", filter_level.to_string());
         }
 
+        if self.delimited {
+            add_param(&mut params, "delimited", "length");
+        }
+
         if !self.follow.is_empty() {
             let to_follow = self
                 .follow
@@ -477,7 +542,10 @@ impl StreamBuilder {
 
         let req = auth::post(self.url, token, Some(&params));
 
-        TwitterStream::new(req)
+        TwitterStream {
+            delimited: self.delimited,
+            ..TwitterStream::new(req)
+        }
     }
 }
 
@@ -488,6 +556,29 @@ pub fn filter() -> StreamBuilder {
 
 /// Opens a `TwitterStream` returning "a small random sample of all public statuses".
 ///
+/// This is roughly 1% of all public tweets, delivered as they're posted. Messages from this stream
+/// are parsed the same way as the ones from [`filter`], so the same `StreamMessage` handling works
+/// for both.
+///
+/// ### Example
+/// ```rust,no_run
+/// # fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// use egg_mode::stream::{sample, StreamMessage};
+/// use tokio::runtime::current_thread::block_on_all;
+/// use futures::Stream;
+///
+/// let stream = sample(&token);
+///
+/// block_on_all(stream.for_each(|m| {
+///     if let StreamMessage::Tweet(tweet) = m {
+///         println!("{}", tweet.text);
+///     }
+///     futures::future::ok(())
+/// })).expect("Stream error");
+/// # }
+/// ```
+///
 /// As sample streams don't have the same configuration options as filter streams,
 /// this directly returns a `TwitterStream`, rather than going through a [`StreamBuilder`]. To apply
 /// filter options on the public stream, start with [`filter`] and add parameters to the
@@ -546,8 +637,8 @@ mod tests {
     #[test]
     fn parse_tweet_stream() {
         let msg = load_stream("sample_payloads/sample-stream.json");
-        if let StreamMessage::Tweet(_tweet) = msg {
-            // OK
+        if let StreamMessage::Tweet(tweet) = msg {
+            assert_eq!(tweet.filter_level, Some(FilterLevel::Low));
         } else {
             panic!("Not a tweet")
         }
@@ -562,4 +653,63 @@ mod tests {
             panic!("Not a ping")
         }
     }
+
+    #[test]
+    fn parse_compliance_messages() {
+        let msg = StreamMessage::from_str(
+            r#"{"delete":{"status":{"id":1234,"id_str":"1234","user_id":3,"user_id_str":"3"}}}"#,
+        )
+        .unwrap();
+        match msg {
+            StreamMessage::Delete { status_id, user_id } => {
+                assert_eq!(status_id, 1234);
+                assert_eq!(user_id, 3);
+            }
+            other => panic!("Not a delete: {:?}", other),
+        }
+
+        let msg = StreamMessage::from_str(
+            r#"{"scrub_geo":{"user_id":14090452,"user_id_str":"14090452",
+                "up_to_status_id":23260136625,"up_to_status_id_str":"23260136625"}}"#,
+        )
+        .unwrap();
+        match msg {
+            StreamMessage::ScrubGeo {
+                user_id,
+                up_to_status_id,
+            } => {
+                assert_eq!(user_id, 14090452);
+                assert_eq!(up_to_status_id, 23260136625);
+            }
+            other => panic!("Not a scrub_geo: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn length_delimited_framing() {
+        let mut stream = TwitterStream {
+            delimited: true,
+            ..TwitterStream::new(Ok(Request::new(Body::empty())))
+        };
+        let msg = "{\"delete\":{\"status\":{\"id\":10,\"user_id\":20}}}\r\n";
+        let framed = format!("\r\n{}\r\n{}", msg.len(), msg);
+        let (first, second) = framed.as_bytes().split_at(framed.len() - 10);
+
+        stream.buf.extend(first);
+        match stream.next_message() {
+            Some(Ok(StreamMessage::Ping)) => (),
+            other => panic!("Not a ping: {:?}", other),
+        }
+        assert!(stream.next_message().is_none());
+
+        stream.buf.extend(second);
+        match stream.next_message() {
+            Some(Ok(StreamMessage::Delete { status_id, user_id })) => {
+                assert_eq!(status_id, 10);
+                assert_eq!(user_id, 20);
+            }
+            other => panic!("Not a delete: {:?}", other),
+        }
+        assert!(stream.buf.is_empty());
+    }
 }
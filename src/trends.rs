@@ -0,0 +1,284 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Structs and methods for loading trending topics.
+//!
+//! Twitter tracks trending topics per location, using Yahoo! "Where On Earth" IDs (WOEIDs) to
+//! identify each location. The WOEID `1` refers to worldwide trends. To load the current trends
+//! for a location, call `place` with its WOEID.
+//!
+//! Trends are only updated every few minutes, so applications that check them often can use a
+//! `TrendsCache` with `place_cached` to reuse the last set of trends loaded for a location until
+//! a given amount of time has passed.
+//!
+//! To find the WOEID for a location, call `closest` with its coordinates. To go straight from a
+//! `Place` to its trends, use `place::trends_for`.
+//!
+//! ```rust,no_run
+//! # use egg_mode::Token;
+//! use tokio::runtime::current_thread::block_on_all;
+//! # fn main() {
+//! # let token: Token = unimplemented!();
+//! use egg_mode::trends;
+//!
+//! let worldwide = block_on_all(trends::place(1, &token)).unwrap();
+//!
+//! for trend in &worldwide.trends {
+//!     println!("{}", trend.name);
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono;
+use futures_core::Future;
+use futures_util::future::{self, Either};
+use futures_util::FutureExt;
+use serde::Deserialize;
+
+use crate::{auth, error, links};
+use crate::common::*;
+use crate::error::Error::InvalidResponse;
+
+///Load the current trending topics for the location with the given WOEID.
+///
+///The WOEID `1` returns worldwide trends. Twitter caches trends for each location for about five
+///minutes, so requesting them more often than that will return the same data; see
+///`place_cached` for a way to avoid those redundant calls.
+pub fn place(woeid: u32, token: &auth::Token) -> FutureResponse<TrendsResult> {
+    let mut params = HashMap::new();
+    add_param(&mut params, "id", woeid.to_string());
+
+    let req = auth::get(links::trends::PLACE, token, Some(&params));
+
+    fn parse_trends(
+        full_resp: String,
+        headers: &Headers,
+    ) -> Result<Response<TrendsResult>, error::Error> {
+        let ret: Response<Vec<TrendsResult>> = make_response(full_resp, headers)?;
+        let mut results = ret.response.into_iter();
+
+        let trends = results
+            .next()
+            .ok_or_else(|| InvalidResponse("Missing trends for location", None))?;
+        Ok(Response {
+            rate_limit: ret.rate_limit,
+            rate_limit_remaining: ret.rate_limit_remaining,
+            rate_limit_reset: ret.rate_limit_reset,
+            server_time: ret.server_time,
+            etag: ret.etag,
+            content_length: ret.content_length,
+            meta: ret.meta,
+            response: trends,
+        })
+    }
+
+    make_future(req, parse_trends)
+}
+
+///Load the locations with trending topics that are closest to the given coordinate.
+///
+///The locations are sorted by how close they are to the given coordinate, so the first one is the
+///closest. Its `woeid` can be handed to `place` to load its trends.
+pub fn closest(
+    latitude: f64,
+    longitude: f64,
+    token: &auth::Token,
+) -> FutureResponse<Vec<ClosestLocation>> {
+    let mut params = HashMap::new();
+    add_param(&mut params, "lat", latitude.to_string());
+    add_param(&mut params, "long", longitude.to_string());
+
+    let req = auth::get(links::trends::CLOSEST, token, Some(&params));
+
+    make_parsed_future(req)
+}
+
+///Load the current trending topics for the given WOEID, reusing the copy saved in the given cache
+///if it's younger than `ttl`.
+///
+///The age of a cached set of trends is measured from its `as_of` timestamp, i.e. from when Twitter
+///generated it, not from when it was loaded. If the cached copy is too old (or the location hasn't
+///been loaded yet), this calls `place` and saves the result into the cache before returning it.
+///
+///Since a cached result doesn't come from a network call, this future doesn't return rate-limit
+///information like `place` does.
+pub fn place_cached<'c>(
+    woeid: u32,
+    ttl: Duration,
+    cache: &'c mut TrendsCache,
+    token: &auth::Token,
+) -> impl Future<Output = Result<TrendsResult, error::Error>> + 'c {
+    if let Some(trends) = cache.get(woeid, ttl).cloned() {
+        Either::Left(future::ready(Ok(trends)))
+    } else {
+        let loader = place(woeid, token);
+        Either::Right(
+            loader.map(move |resp: Result<Response<TrendsResult>, error::Error>| {
+                let trends = resp?.response;
+                cache.insert(woeid, trends.clone());
+                Ok(trends)
+            }),
+        )
+    }
+}
+
+///A set of trending topics for a location, as returned by `place`.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct TrendsResult {
+    ///The trending topics themselves.
+    pub trends: Vec<Trend>,
+    ///UTC timestamp of when Twitter generated this set of trends.
+    pub as_of: chrono::DateTime<chrono::Utc>,
+    ///UTC timestamp of when Twitter started tracking these trends.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    ///The locations these trends apply to.
+    pub locations: Vec<TrendLocation>,
+}
+
+///A single trending topic.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct Trend {
+    ///The name of the trend, as displayed on Twitter. This may be a hashtag or a phrase.
+    pub name: String,
+    ///A link to a Twitter search for this trend.
+    pub url: String,
+    ///The search query for this trend, in URL-encoded form.
+    pub query: String,
+    ///If present, indicates that this trend is a promoted topic.
+    pub promoted_content: Option<bool>,
+    ///The number of tweets about this trend in the last 24 hours, if Twitter has that number.
+    pub tweet_volume: Option<u64>,
+}
+
+///A location that a set of trends applies to.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct TrendLocation {
+    ///The name of the location.
+    pub name: String,
+    ///The WOEID of the location.
+    pub woeid: u32,
+}
+
+///A location that Twitter has trending topics for, as returned by `closest`.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct ClosestLocation {
+    ///The name of the location.
+    pub name: String,
+    ///The WOEID of the location.
+    pub woeid: u32,
+    ///The WOEID of the location containing this one.
+    #[serde(rename = "parentid")]
+    pub parent_id: u32,
+    ///The name of the country containing this location.
+    pub country: String,
+    ///The two-letter code of the country containing this location, or `None` for the worldwide
+    ///location.
+    #[serde(rename = "countryCode")]
+    pub country_code: Option<String>,
+    ///The kind of location this is.
+    #[serde(rename = "placeType")]
+    pub place_type: LocationType,
+    ///A link to Yahoo!'s information about this location.
+    pub url: String,
+}
+
+///The kind of location a `ClosestLocation` represents, e.g. a town or a country.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct LocationType {
+    ///The numeric code Yahoo! uses for this kind of location.
+    pub code: u32,
+    ///The name of this kind of location, e.g. `"Town"` or `"Country"`.
+    pub name: String,
+}
+
+///A cache of trending topics by location, for use with `place_cached`.
+///
+///This only saves the most recent set of trends for each WOEID. It's up to you to keep it around
+///between calls; a new cache is empty, and `place_cached` will always load from Twitter when given
+///an empty cache.
+#[derive(Debug, Clone, Default)]
+pub struct TrendsCache {
+    trends: HashMap<u32, TrendsResult>,
+}
+
+impl TrendsCache {
+    ///Creates a new, empty `TrendsCache`.
+    pub fn new() -> TrendsCache {
+        TrendsCache::default()
+    }
+
+    ///Returns the saved trends for the given WOEID, if they were generated less than `ttl` ago.
+    pub fn get(&self, woeid: u32, ttl: Duration) -> Option<&TrendsResult> {
+        let ttl =
+            chrono::Duration::from_std(ttl).unwrap_or_else(|_| chrono::Duration::max_value());
+        let now = chrono::Utc::now();
+
+        self.trends
+            .get(&woeid)
+            .filter(|trends| now.signed_duration_since(trends.as_of) < ttl)
+    }
+
+    ///Saves the given trends for the given WOEID, replacing any trends saved for it previously.
+    pub fn insert(&mut self, woeid: u32, trends: TrendsResult) {
+        self.trends.insert(woeid, trends);
+    }
+
+    ///Removes all the saved trends.
+    pub fn clear(&mut self) {
+        self.trends.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{TrendsCache, TrendsResult};
+    use crate::common::tests::load_file;
+
+    fn load_trends() -> TrendsResult {
+        let sample = load_file("sample_payloads/sample-trends.json");
+        ::serde_json::from_str(&sample).unwrap()
+    }
+
+    #[test]
+    fn parse_trends() {
+        let trends = load_trends();
+
+        assert_eq!(trends.trends.len(), 2);
+        assert_eq!(trends.trends[0].tweet_volume, Some(12345));
+        assert_eq!(trends.locations[0].woeid, 1);
+    }
+
+    #[test]
+    fn trends_cache_ttl() {
+        let mut fresh = load_trends();
+        fresh.as_of = chrono::Utc::now() - chrono::Duration::minutes(1);
+        let mut stale = load_trends();
+        stale.as_of = chrono::Utc::now() - chrono::Duration::minutes(10);
+
+        let mut cache = TrendsCache::new();
+        cache.insert(1, fresh);
+        cache.insert(2, stale);
+
+        let ttl = Duration::from_secs(5 * 60);
+        assert!(cache.get(1, ttl).is_some());
+        assert!(cache.get(2, ttl).is_none());
+        assert!(cache.get(3, ttl).is_none());
+
+        //a longer TTL accepts the older set too
+        assert!(cache.get(2, Duration::from_secs(15 * 60)).is_some());
+
+        cache.clear();
+        assert!(cache.get(1, ttl).is_none());
+    }
+}
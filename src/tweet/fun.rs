@@ -6,22 +6,38 @@ use crate::common::*;
 use crate::error::Error::InvalidResponse;
 use crate::user::UserID;
 use crate::{auth, cursor, links};
+use futures_core::Stream;
+use futures_util::{stream, StreamExt};
 use serde_json;
 use std::collections::HashMap;
 
 use super::*;
 
 ///Lookup a single tweet by numeric ID.
+///
+///This fills in `current_user_retweet` if the authenticated user has retweeted the tweet. To set
+///other options for the call, use `show_with` instead.
 pub fn show(id: u64, token: &auth::Token) -> FutureResponse<Tweet> {
-    let mut params = HashMap::new();
-    add_param(&mut params, "id", id.to_string());
-    add_param(&mut params, "include_my_retweet", "true");
-    add_param(&mut params, "tweet_mode", "extended");
-    add_param(&mut params, "include_ext_alt_text", "true");
-
-    let req = auth::get(links::statuses::SHOW, token, Some(&params));
+    show_with(id).call(token)
+}
 
-    make_parsed_future(req)
+///Begins building a lookup of a single tweet by numeric ID, with optional parameters.
+///
+///## Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///use tokio::runtime::current_thread::block_on_all;
+///# fn main() {
+///# let token: Token = unimplemented!();
+///let tweet = block_on_all(egg_mode::tweet::show_with(1234)
+///                             .trim_user(true)
+///                             .call(&token))
+///                .unwrap();
+///# }
+///```
+pub fn show_with(id: u64) -> ShowBuilder {
+    ShowBuilder::new(id)
 }
 
 ///Lookup the most recent 100 (or fewer) retweets of the given tweet.
@@ -193,6 +209,68 @@ pub fn user_timeline<'a, T: Into<UserID<'a>>>(
     Timeline::new(links::statuses::USER_TIMELINE, Some(params), token)
 }
 
+///The number of most recent tweets Twitter will load from a user's timeline.
+const USER_TIMELINE_LIMIT: usize = 3200;
+
+///Load every tweet Twitter will return from the given user's timeline, from newest to oldest.
+///
+///This wraps a `user_timeline` (including replies and retweets) with the maximum page size, and
+///repeatedly calls `older` on it, yielding each tweet as it's loaded. Since Twitter only loads the
+///most recent 3,200 tweets of a timeline, the stream ends once it has returned that many tweets, or
+///when Twitter returns an empty page, whichever comes first. Reaching that limit is not treated as
+///an error.
+///
+///If a page fails to load, the stream yields that error and then ends.
+///
+///## Example
+///
+///```rust,no_run
+///# use egg_mode::Token;
+///use futures_util::StreamExt;
+///use tokio::runtime::current_thread::block_on_all;
+///# fn main() {
+///# let token: Token = unimplemented!();
+///let tweets = egg_mode::tweet::user_timeline_backfill("rustlang", &token);
+///let tweets = block_on_all(tweets.collect::<Vec<_>>());
+///
+///for tweet in tweets {
+///    println!("{}", tweet.unwrap().text);
+///}
+///# }
+///```
+pub fn user_timeline_backfill<'a, T: Into<UserID<'a>>>(
+    acct: T,
+    token: &auth::Token,
+) -> impl Stream<Item = Result<Tweet, error::Error>> + 'a {
+    let timeline = user_timeline(acct, true, true, token).with_page_size(200);
+
+    stream::unfold(Some((timeline, 0)), |state| match state {
+        None => Either::Left(future::ready(None)),
+        Some((timeline, seen)) => Either::Right(timeline.older(None).map(move |page| {
+            match page {
+                Ok((timeline, resp)) => {
+                    if resp.response.is_empty() {
+                        return None;
+                    }
+
+                    let remaining = USER_TIMELINE_LIMIT - seen;
+                    let tweets = resp.response.into_iter().take(remaining);
+                    let seen = seen + tweets.len();
+                    let next = if seen < USER_TIMELINE_LIMIT {
+                        Some((timeline, seen))
+                    } else {
+                        None
+                    };
+
+                    Some((tweets.map(Ok).collect::<Vec<_>>(), next))
+                }
+                Err(err) => Some((vec![Err(err)], None)),
+            }
+        })),
+    })
+    .flat_map(stream::iter)
+}
+
 ///Make a `Timeline` struct for navigating the collection of tweets posted by the authenticated
 ///user that have been retweeted by others.
 ///
@@ -228,7 +306,7 @@ pub fn retweet(id: u64, token: &auth::Token) -> FutureResponse<Tweet> {
 ///Unretweet the given status as the authenticated user.
 ///
 ///The given ID may either be the original status, or the ID of the authenticated user's retweet of
-///it.
+///it, as given in the original tweet's `current_user_retweet` field.
 ///
 ///On success, the future returned by this function yields the original tweet.
 pub fn unretweet(id: u64, token: &auth::Token) -> FutureResponse<Tweet> {
@@ -281,3 +359,81 @@ pub fn delete(id: u64, token: &auth::Token) -> FutureResponse<Tweet> {
 
     make_parsed_future(req)
 }
+
+///Post the given drafts as a thread, with each tweet replying to the one before it.
+///
+///The first draft is posted as-is. Each draft after it has its `in_reply_to` set to the tweet
+///posted before it, and `auto_populate_reply_metadata` set to `true` unless the draft already sets
+///it. The drafts are posted one at a time, in order.
+///
+///On success, the future returned by this function yields the posted tweets, in thread order. If
+///posting one of the drafts fails, the thread stops there, and the returned `ThreadError` contains
+///both the error and the tweets that were already posted, so the rest of the thread can be posted
+///later.
+pub fn post_thread<'a>(drafts: Vec<DraftTweet<'a>>, token: &auth::Token) -> ThreadFuture<'a> {
+    let mut fut = ThreadFuture {
+        token: token.clone(),
+        drafts: drafts.into_iter(),
+        loader: None,
+        posted: Vec::new(),
+    };
+    fut.send_next();
+    fut
+}
+
+///Mute the conversation containing the given tweet, so the authenticated user stops getting
+///notifications about replies to it.
+///
+///The given ID can be any tweet in the conversation. Muting a conversation that's already muted is
+///treated as a success, so this is safe to call again.
+pub fn mute_conversation(id: u64, token: &auth::Token) -> FutureResponse<()> {
+    conversation_mute_request(links::statuses::MUTE_CONVERSATION, id, token)
+}
+
+///Unmute the conversation containing the given tweet, so the authenticated user gets notifications
+///about replies to it again.
+///
+///Unmuting a conversation that isn't muted is treated as a success, so this is safe to call again.
+pub fn unmute_conversation(id: u64, token: &auth::Token) -> FutureResponse<()> {
+    conversation_mute_request(links::statuses::UNMUTE_CONVERSATION, id, token)
+}
+
+fn conversation_mute_request(
+    link: &'static str,
+    id: u64,
+    token: &auth::Token,
+) -> FutureResponse<()> {
+    let mut params = HashMap::new();
+    add_param(&mut params, "tweet_id", id.to_string());
+
+    let req = auth::post(link, token, Some(&params));
+
+    make_future_with_errors(req, parse_mute_conversation)
+}
+
+///Parses the response from `mute_conversation` or `unmute_conversation`, turning Twitter's error
+///for a conversation that was already muted (or already unmuted) into a success.
+///
+///Twitter doesn't document specific error codes for these, so this checks the error message.
+pub(super) fn parse_mute_conversation(
+    full_resp: String,
+    headers: &Headers,
+) -> Result<Response<()>, error::Error> {
+    if let Ok(mut terrs) = serde_json::from_str::<error::TwitterErrors>(&full_resp) {
+        if !is_mute_unchanged(&terrs) {
+            terrs.meta = response_meta(headers);
+            return Err(error::Error::TwitterError(terrs));
+        }
+    }
+
+    rate_headers(headers)
+}
+
+///Returns whether the given errors are how Twitter reports that a conversation was already muted
+///(or already unmuted).
+fn is_mute_unchanged(errs: &error::TwitterErrors) -> bool {
+    errs.errors.iter().any(|e| {
+        let message = e.message.to_lowercase();
+        message.contains("already muted") || message.contains("not muted")
+    })
+}
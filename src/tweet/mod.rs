@@ -15,12 +15,20 @@
 //!
 //! - `Tweet`/`TweetEntities`/`ExtendedTweetEntities`: At the bottom of it all, this is the struct
 //!   that represents a single tweet. The `*Entities` structs contain information about media,
-//!   links, and hashtags within their parent tweet.
+//!   links, and hashtags within their parent tweet. `Engagement` summarizes the interactions on a
+//!   tweet, and `TweetLocation` summarizes where it was posted from.
+//! - `TweetRef`: A lightweight reference to a tweet by its ID, which can be loaded back into a
+//!   full `Tweet` when needed.
+//! - `ShowBuilder`: Returned by `show_with`, this loads a single tweet with optional parameters.
 //! - `DraftTweet`: This is what you use to post a new tweet. At present, not all available options
 //!   are supported, but basics like marking the tweet as a reply and attaching a location
-//!   coordinate are available.
+//!   coordinate are available. `RecentPosts` remembers recently-sent drafts so that retried posts
+//!   aren't sent twice. `ThreadFuture` posts several drafts as a reply chain, and `ThreadError`
+//!   returns the tweets it managed to post if it fails partway through.
 //! - `Timeline`: Returned by several functions in this module, this is how you cursor through a
-//!   collection of tweets. See the struct-level documentation for details.
+//!   collection of tweets. See the struct-level documentation for details. `EmptyPage` describes
+//!   why a `Timeline` may have loaded an empty page. `TimelinePosition` saves where a `Timeline`
+//!   has loaded to, so it can be restored later.
 //!
 //! ## Functions
 //!
@@ -30,6 +38,8 @@
 //! authenticated user's account.
 //!
 //! - `delete` (for creating a tweet, see `DraftTweet`)
+//! - `post_thread`
+//! - `mute_conversation`/`unmute_conversation`
 //! - `like`/`unlike`
 //! - `retweet`/`unretweet`
 //!
@@ -38,7 +48,7 @@
 //! These functions either perform some direct lookup of specific tweets, or provide some metadata
 //! about the given tweet in a direct (non-`Timeline`) fashion.
 //!
-//! - `show`
+//! - `show`/`show_with`
 //! - `lookup`/`lookup_map` (for the differences between these functions, see their respective
 //!   documentations.)
 //! - `retweeters_of`
@@ -52,24 +62,30 @@
 //!
 //! - `home_timeline`/`mentions_timeline`/`retweets_of_me`
 //! - `user_timeline`/`liked_by`
+//!
+//! To load everything Twitter will return from a user's timeline without driving the `Timeline`
+//! yourself, `user_timeline_backfill` returns a `Stream` of its tweets, oldest last.
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::pin::Pin;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use chrono;
 use futures_core::{Future, Poll};
 use futures_core::task::Context;
+use futures_util::future::{self, Either};
 use futures_util::FutureExt;
 use hyper::{Body, Request};
 use regex::Regex;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde::de::Error;
 
-use crate::{auth, entities, error, links, place, user};
+use crate::{auth, entities, error, links, place, service, user};
 use crate::common::*;
 use crate::error::Error::InvalidResponse;
+use crate::media::{MediaCategory, MediaHandle};
 use crate::stream::FilterLevel;
 
 pub use self::fun::*;
@@ -92,9 +108,10 @@ mod raw;
 ///* `user`
 ///* `source`
 ///* `favorite_count`/`retweet_count`
+///* `reply_count`/`quote_count`, though these are only given to certain API access levels.
 ///* `lang`, though third-party clients usually don't surface this at a user-interface level.
 ///  Twitter Web uses this to create machine-translations of the tweet.
-///* `coordinates`/`place`
+///* `coordinates`/`geo`/`place` (see `location` for a combined view of these)
 ///* `display_text_range`
 ///* `truncated`
 ///
@@ -116,6 +133,7 @@ mod raw;
 ///
 ///* `in_reply_to_user_id`/`in_reply_to_screen_name`
 ///* `in_reply_to_status_id`
+///* `conversation_id` (see `conversation_id_or_self` for a fallback when it's missing)
 ///
 ///## Retweets and Quote Tweets
 ///
@@ -142,6 +160,8 @@ mod raw;
 ///  GIFs, note that `entities` will only contain a thumbnail, and the actual video links will be
 ///  in this field. For tweets with more than one photo attached, `entities` will only contain the
 ///  first photo, and this field will contain all of them.
+///* `card_uri`: This field is only present when the tweet was loaded with `include_card_uri` set
+///  (see `Timeline::include_cards`), and the tweet has a link card.
 ///* `possibly_sensitive`
 ///* `withheld_copyright`
 ///* `withheld_in_countries`
@@ -151,16 +171,34 @@ pub struct Tweet {
     //If the user has contributors enabled, this will show which accounts contributed to this
     //tweet.
     //pub contributors: Option<Contributors>,
-    ///If present, the location coordinate attached to the tweet, as a (latitude, longitude) pair.
+    ///If the tweet has a link card and was loaded with `include_card_uri` set, the URI of the card.
+    pub card_uri: Option<String>,
+    ///If present, the ID of the tweet that started the conversation this tweet is part of.
+    ///
+    ///Every tweet in a thread, including the first one, has the same `conversation_id`, so it can be
+    ///used to group tweets into threads without following `in_reply_to_status_id` back to the
+    ///start. Only newer payloads include this; otherwise, this is `None`.
+    pub conversation_id: Option<u64>,
+    ///If present, the location coordinate attached to the tweet, as a (longitude, latitude) pair.
+    ///
+    ///Note that this is the order Twitter gives these coordinates in, which is the opposite of the
+    ///order in `geo`. See `location` for a way to get the coordinates without worrying about this.
     pub coordinates: Option<(f64, f64)>,
     ///UTC timestamp from when the tweet was posted.
     pub created_at: chrono::DateTime<chrono::Utc>,
     ///If the authenticated user has retweeted this tweet, contains the ID of the retweet.
+    ///
+    ///This can be handed to `unretweet` to undo the retweet. Twitter only includes this when the
+    ///tweet is loaded with `include_my_retweet` set, like with `show`; otherwise, and when the
+    ///authenticated user hasn't retweeted this tweet, this is `None`.
     pub current_user_retweet: Option<u64>,
     ///If this tweet is an extended tweet with "hidden" metadata and entities, contains the byte
-    ///offsets between which the "displayable" tweet text is.
+    ///offsets between which the "displayable" tweet text is. See `display_text` for a shortcut to
+    ///get that text directly.
     pub display_text_range: Option<(usize, usize)>,
     ///Link, hashtag, and user mention information extracted from the tweet text.
+    ///
+    ///If the tweet was loaded with `include_entities` set to `false`, this will be empty.
     pub entities: TweetEntities,
     ///Extended media information attached to the tweet, if media is available.
     ///
@@ -176,6 +214,11 @@ pub struct Tweet {
     ///Indicates the maximum `FilterLevel` parameter that can be applied to a stream and still show
     ///this tweet.
     pub filter_level: Option<FilterLevel>,
+    ///If present, the location coordinate attached to the tweet, as a (latitude, longitude) pair.
+    ///
+    ///This is a deprecated field that Twitter still returns, which contains the same point as
+    ///`coordinates`, but in the opposite order.
+    pub geo: Option<(f64, f64)>,
     ///Numeric ID for this tweet.
     pub id: u64,
     ///If the tweet is a reply, contains the ID of the user that was replied to.
@@ -197,6 +240,14 @@ pub struct Tweet {
     pub quoted_status_id: Option<u64>,
     ///If this tweet is quoting another by link, contains the quoted tweet.
     pub quoted_status: Option<Box<Tweet>>,
+    ///The number of times this tweet has been quoted by other tweets.
+    ///
+    ///This is only given to certain API access levels, and will be `None` otherwise.
+    pub quote_count: Option<u64>,
+    ///The number of replies this tweet has received.
+    ///
+    ///This is only given to certain API access levels, and will be `None` otherwise.
+    pub reply_count: Option<u64>,
     //"A set of key-value pairs indicating the intended contextual delivery of the containing
     //Tweet. Currently used by Twitter’s Promoted Products."
     //pub scopes: Option<Scopes>,
@@ -215,9 +266,16 @@ pub struct Tweet {
     ///The text of the tweet. For "extended" tweets, opening reply mentions and/or attached media
     ///or quoted tweet links do not count against character count, so this could be longer than 280
     ///characters in those situations.
+    ///
+    ///This is filled from `full_text` or `extended_tweet` if Twitter sent them, falling back to the
+    ///classic `text` field, so it holds the longest text Twitter gave regardless of how the tweet
+    ///was loaded. For retweets, use `original` to get the untruncated text of the retweeted tweet.
     pub text: String,
     ///Indicates whether this tweet is a truncated "compatibility" form of an extended tweet whose
     ///full text is longer than 280 characters.
+    ///
+    ///If Twitter sent the full tweet alongside the truncated one in an `extended_tweet` field, as
+    ///the Streaming API does, the full text and entities are loaded instead, and this is `false`.
     pub truncated: bool,
     ///The user who posted this tweet. This field will be absent on tweets included as part of a
     ///`TwitterUser`.
@@ -241,9 +299,23 @@ impl<'de> Deserialize<'de> for Tweet {
         D: Deserializer<'de>,
     {
         let mut raw = raw::RawTweet::deserialize(deser)?;
+
+        //tweets from the Streaming API keep the full text and entities of long tweets in
+        //`extended_tweet`, with a truncated version at the top level; swap the full one in
+        if raw.full_text.is_none() {
+            if let Some(xt) = raw.extended_tweet.take() {
+                raw.full_text = Some(xt.full_text);
+                raw.display_text_range = xt.display_text_range;
+                raw.entities = xt.entities;
+                if xt.extended_entities.is_some() {
+                    raw.extended_entities = xt.extended_entities;
+                }
+                raw.truncated = false;
+            }
+        }
+
         let text = raw
             .full_text
-            .or(raw.extended_tweet.map(|xt| xt.full_text))
             .or(raw.text)
             .ok_or_else(|| D::Error::custom("Tweet missing text field"))?;
         let current_user_retweet = raw.current_user_retweet.map(|cur| cur.id);
@@ -275,6 +347,8 @@ impl<'de> Deserialize<'de> for Tweet {
         }
 
         Ok(Tweet {
+            card_uri: raw.card_uri,
+            conversation_id: raw.conversation_id,
             coordinates: raw.coordinates.map(|coords| coords.coordinates),
             created_at: raw.created_at,
             display_text_range: raw.display_text_range,
@@ -283,6 +357,7 @@ impl<'de> Deserialize<'de> for Tweet {
             favorite_count: raw.favorite_count,
             favorited: raw.favorited,
             filter_level: raw.filter_level,
+            geo: raw.geo.map(|geo| geo.coordinates),
             id: raw.id,
             in_reply_to_user_id: raw.in_reply_to_user_id,
             in_reply_to_screen_name: raw.in_reply_to_screen_name,
@@ -292,6 +367,8 @@ impl<'de> Deserialize<'de> for Tweet {
             possibly_sensitive: raw.possibly_sensitive,
             quoted_status_id: raw.quoted_status_id,
             quoted_status: raw.quoted_status,
+            quote_count: raw.quote_count,
+            reply_count: raw.reply_count,
             retweet_count: raw.retweet_count,
             retweeted: raw.retweeted,
             retweeted_status: raw.retweeted_status,
@@ -307,6 +384,301 @@ impl<'de> Deserialize<'de> for Tweet {
     }
 }
 
+impl Tweet {
+    ///Returns the portion of the tweet text that should be displayed, as given by
+    ///`display_text_range`.
+    ///
+    ///For replies and tweets with attached media, the full `text` includes the @mentions that
+    ///the tweet is replying to, and/or a trailing link to the attached media. Twitter's own clients
+    ///hide those parts of the text, displaying the mentions and media separately. This returns the
+    ///text without them, or the full `text` if the tweet doesn't have a `display_text_range`.
+    pub fn display_text(&self) -> &str {
+        self.display_text_range
+            .and_then(|(start, end)| self.text.get(start..end))
+            .unwrap_or(&self.text)
+    }
+
+    ///Collects the location information attached to this tweet into one `TweetLocation`.
+    ///
+    ///The coordinates are taken from `coordinates` if present, otherwise from `geo`, and are
+    ///returned as separate latitude and longitude values so they can't be mixed up. If the tweet
+    ///has neither but has a `place` attached, the center of the place's bounding box is used
+    ///instead, and `exact` is set to `false`. If the tweet has no location information, this
+    ///returns `None`.
+    pub fn location(&self) -> Option<TweetLocation> {
+        let point = self.coordinates.map(|(lon, lat)| (lat, lon)).or(self.geo);
+
+        if let Some((lat, lon)) = point {
+            return Some(TweetLocation {
+                lat,
+                lon,
+                exact: true,
+                place: self.place.clone(),
+            });
+        }
+
+        let place = self.place.as_ref()?;
+        let (lat, lon) = place.centroid()?;
+
+        Some(TweetLocation {
+            lat,
+            lon,
+            exact: false,
+            place: Some(place.clone()),
+        })
+    }
+
+    ///Returns a `TweetRef` pointing to this tweet, so the full `Tweet` can be dropped and loaded
+    ///again later.
+    pub fn dehydrate(&self) -> TweetRef {
+        TweetRef { id: self.id }
+    }
+
+    ///Returns whether this tweet is a retweet, i.e. whether `retweeted_status` is present.
+    pub fn is_retweet(&self) -> bool {
+        self.retweeted_status.is_some()
+    }
+
+    ///Returns the tweet whose content should be displayed for this one.
+    ///
+    ///For retweets, the outer tweet's `text` is a truncated copy of the original, prefixed with
+    ///"RT @user: ", and its entities only cover that truncated text. This returns the original
+    ///tweet from `retweeted_status` if present, so its full text and entities can be shown
+    ///instead. For any other tweet, this returns `self`.
+    pub fn original(&self) -> &Tweet {
+        self.retweeted_status.as_ref().map_or(self, |rt| &**rt)
+    }
+
+    ///Returns the ID of the conversation this tweet is part of, falling back to this tweet's own ID
+    ///if it isn't a reply.
+    ///
+    ///If Twitter included `conversation_id`, this returns it. Otherwise, a tweet that isn't a
+    ///reply starts its own conversation, so its own `id` is returned. For replies without a
+    ///`conversation_id`, the start of the conversation isn't known, so this returns `None`.
+    pub fn conversation_id_or_self(&self) -> Option<u64> {
+        self.conversation_id.or_else(|| {
+            if self.in_reply_to_status_id.is_none() {
+                Some(self.id)
+            } else {
+                None
+            }
+        })
+    }
+
+    ///If this tweet is a reply, loads the tweet it's replying to.
+    ///
+    ///This returns `None` if `in_reply_to_status_id` is empty. Otherwise, it returns the result of
+    ///calling `show` with that ID. Note that the parent tweet may have been deleted or be
+    ///otherwise unavailable, in which case the returned future will yield an error.
+    pub fn in_reply_to(&self, token: &auth::Token) -> Option<FutureResponse<Tweet>> {
+        self.in_reply_to_status_id.map(|id| show(id, token))
+    }
+
+    ///Reloads this tweet and updates the authenticated user's interactions with it, along with its
+    ///interaction counts, in place.
+    ///
+    ///`favorited`, `retweeted`, and `current_user_retweet` only reflect the authenticated user's
+    ///actions as of when the tweet was loaded, so they go stale after liking or retweeting it. This
+    ///calls `show` and copies those fields and `favorite_count`, `retweet_count`, `quote_count`,
+    ///and `reply_count` from the fresh copy, leaving the rest of the tweet alone.
+    pub fn refresh_viewer_state<'a>(
+        &'a mut self,
+        token: &auth::Token,
+    ) -> impl Future<Output = Result<Response<()>, error::Error>> + 'a {
+        show(self.id, token).map(move |resp| {
+            let resp = resp?;
+            self.update_viewer_state(&resp.response);
+            Ok(Response::map(resp, |_| ()))
+        })
+    }
+
+    fn update_viewer_state(&mut self, fresh: &Tweet) {
+        self.favorited = fresh.favorited;
+        self.retweeted = fresh.retweeted;
+        self.current_user_retweet = fresh.current_user_retweet;
+        self.favorite_count = fresh.favorite_count;
+        self.retweet_count = fresh.retweet_count;
+        self.quote_count = fresh.quote_count;
+        self.reply_count = fresh.reply_count;
+    }
+
+    ///Collects the interaction counts on this tweet, along with the authenticated user's own
+    ///interactions with it, into one `Engagement` struct.
+    pub fn engagement(&self) -> Engagement {
+        Engagement {
+            retweets: self.retweet_count.max(0) as u64,
+            likes: self.favorite_count.max(0) as u64,
+            replies: self.reply_count,
+            quotes: self.quote_count,
+            retweeted: self.retweeted,
+            liked: self.favorited,
+        }
+    }
+}
+
+///A summary of how users have interacted with a tweet, as returned by `Tweet::engagement`.
+///
+///The `retweeted` and `liked` fields are from the perspective of the authenticated user, and are
+///only present in contexts where Twitter compares the tweet against that user. `replies` and
+///`quotes` are only given to certain API access levels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Engagement {
+    ///The number of times the tweet has been retweeted.
+    pub retweets: u64,
+    ///The number of times the tweet has been liked.
+    pub likes: u64,
+    ///The number of replies to the tweet, if available.
+    pub replies: Option<u64>,
+    ///The number of times the tweet has been quoted, if available.
+    pub quotes: Option<u64>,
+    ///Indicates whether the authenticated user has retweeted the tweet.
+    pub retweeted: Option<bool>,
+    ///Indicates whether the authenticated user has liked the tweet.
+    pub liked: Option<bool>,
+}
+
+impl Engagement {
+    ///Returns the sum of all the interaction counts, treating unavailable counts as zero.
+    pub fn total_interactions(&self) -> u64 {
+        self.retweets + self.likes + self.replies.unwrap_or(0) + self.quotes.unwrap_or(0)
+    }
+}
+
+///Represents a `show` call before it is sent, as returned by `show_with`.
+///
+///This loads the same tweet as `show`, but allows setting the optional parameters Twitter accepts
+///for it. Hand your tokens to `call` to load the tweet.
+#[derive(Debug, Clone)]
+pub struct ShowBuilder {
+    id: u64,
+    include_my_retweet: bool,
+    trim_user: bool,
+}
+
+impl ShowBuilder {
+    ///Begins building a `show` call for the tweet with the given ID.
+    fn new(id: u64) -> Self {
+        ShowBuilder {
+            id,
+            include_my_retweet: true,
+            trim_user: false,
+        }
+    }
+
+    ///Sets whether Twitter should fill in `current_user_retweet` on the tweet, if the
+    ///authenticated user has retweeted it. The default is `true`.
+    pub fn include_my_retweet(self, include: bool) -> Self {
+        ShowBuilder {
+            include_my_retweet: include,
+            ..self
+        }
+    }
+
+    ///Sets whether Twitter should leave out the profile of the tweet's author. The default is
+    ///`false`.
+    ///
+    ///If this is set, Twitter only sends the author's ID, so `user` on the loaded tweet will be
+    ///`None`. This makes the response smaller when the author is already known.
+    pub fn trim_user(self, trim: bool) -> Self {
+        ShowBuilder {
+            trim_user: trim,
+            ..self
+        }
+    }
+
+    ///Loads the tweet with the parameters set on this builder.
+    pub fn call(&self, token: &auth::Token) -> FutureResponse<Tweet> {
+        let mut params = HashMap::new();
+        add_param(&mut params, "id", self.id.to_string());
+        add_param(&mut params, "tweet_mode", "extended");
+        add_param(&mut params, "include_ext_alt_text", "true");
+
+        if self.include_my_retweet {
+            add_param(&mut params, "include_my_retweet", "true");
+        }
+
+        if self.trim_user {
+            add_param(&mut params, "trim_user", "true");
+        }
+
+        let req = auth::get(links::statuses::SHOW, token, Some(&params));
+
+        if self.trim_user {
+            make_future(req, parse_trimmed_tweet)
+        } else {
+            make_parsed_future(req)
+        }
+    }
+}
+
+///Parses a tweet loaded with `trim_user` set.
+///
+///Trimmed users only have an ID, so they can't be loaded as a `TwitterUser`. This removes them
+///from the tweet (and any retweeted or quoted tweet inside it) before parsing it, so they load as
+///`None` instead.
+fn parse_trimmed_tweet(
+    full_resp: String,
+    headers: &Headers,
+) -> Result<Response<Tweet>, error::Error> {
+    fn drop_trimmed_users(tweet: &mut serde_json::Value) {
+        if let Some(tweet) = tweet.as_object_mut() {
+            if tweet.get("user").map_or(false, |user| user.get("screen_name").is_none()) {
+                tweet.remove("user");
+            }
+            for key in &["retweeted_status", "quoted_status"] {
+                if let Some(inner) = tweet.get_mut(*key) {
+                    drop_trimmed_users(inner);
+                }
+            }
+        }
+    }
+
+    let mut tweet: serde_json::Value = parse_json(&full_resp)?;
+    drop_trimmed_users(&mut tweet);
+    let tweet: Tweet = serde_json::from_value(tweet)?;
+
+    Ok(Response::map(rate_headers(headers)?, |_| tweet))
+}
+
+///A reference to a tweet by its ID.
+///
+///This is meant for caching large numbers of tweets without holding onto all their data: keep a
+///`TweetRef` (from `Tweet::dehydrate`) instead of the `Tweet`, and load the tweet again with
+///`hydrate` when it's needed. To load many tweets at once, hand their IDs to `lookup`, which loads
+///up to 100 tweets per call instead of one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TweetRef {
+    ///The ID of the referenced tweet.
+    pub id: u64,
+}
+
+impl TweetRef {
+    ///Loads the referenced tweet. This is the same as calling `show` with the tweet's ID.
+    pub fn hydrate(&self, token: &auth::Token) -> FutureResponse<Tweet> {
+        show(self.id, token)
+    }
+}
+
+impl From<u64> for TweetRef {
+    fn from(id: u64) -> TweetRef {
+        TweetRef { id }
+    }
+}
+
+///A summary of where a tweet was posted from, as returned by `Tweet::location`.
+#[derive(Debug, Clone)]
+pub struct TweetLocation {
+    ///The latitude of the tweet's location.
+    pub lat: f64,
+    ///The longitude of the tweet's location.
+    pub lon: f64,
+    ///Indicates whether `lat` and `lon` are the exact point attached to the tweet. If this is
+    ///`false`, they're the center of the bounding box of `place`.
+    pub exact: bool,
+    ///The `Place` attached to the tweet, if any.
+    pub place: Option<place::Place>,
+}
+
 ///Represents the app from which a specific tweet was posted.
 ///
 ///This struct is parsed out of the HTML anchor tag that Twitter returns as part of each tweet.
@@ -381,7 +753,7 @@ where
 ///Note that for media attached to a tweet, this struct will only contain the first image of a
 ///photo set, or a thumbnail of a video or GIF. Full media information is available in the tweet's
 ///`extended_entities` field.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct TweetEntities {
     ///Collection of hashtags parsed from the tweet.
     pub hashtags: Vec<entities::HashtagEntity>,
@@ -485,12 +857,20 @@ pub struct ExtendedTweetEntities {
 /// called like this, it's possible for these methods to return nothing, which will also clear the
 /// `Timeline`'s tracked IDs.
 ///
+/// An empty page doesn't always mean you've reached the end of the timeline, though. Twitter
+/// removes withheld or deleted tweets *after* loading a page, so a range of tweets where everything
+/// was removed will come back empty even if there are older tweets past it. After loading a page,
+/// `empty_page` will tell you whether it was empty, and whether there could be more tweets past it.
+/// Since the empty page cleared the tracked IDs, looking past it is up to you: Twitter doesn't say
+/// how many tweets it removed, so pick a `max_id` lower than the one in
+/// `EmptyPage::PossiblyFiltered` and load from there with `call`.
+///
 /// If you want to manually pull tweets between certain IDs, the baseline `call` function can do
 /// that for you. Keep in mind, though, that `call` doesn't update the `min_id` or `max_id` fields,
 /// so you'll have to set those yourself if you want to follow up with `older` or `newer`.
 pub struct Timeline<'a> {
     ///The URL to request tweets from.
-    link: &'static str,
+    link: Cow<'static, str>,
     ///The token to authorize requests with.
     token: auth::Token,
     ///Optional set of params to include prior to adding timeline navigation parameters.
@@ -503,6 +883,12 @@ pub struct Timeline<'a> {
     pub max_id: Option<u64>,
     ///The smallest/oldest tweet ID returned in the last call to `start`, `older`, or `newer`.
     pub min_id: Option<u64>,
+    ///The `since_id` and `max_id` bounds sent with the last call to `start`, `older`, or `newer`.
+    last_bounds: (Option<u64>, Option<u64>),
+    ///Whether the last page loaded was a full page.
+    last_full: bool,
+    ///Why the last call to `start`, `older`, or `newer` returned no tweets, if it didn't.
+    last_empty: Option<EmptyPage>,
 }
 
 impl<'a> Timeline<'a> {
@@ -510,6 +896,9 @@ impl<'a> Timeline<'a> {
     pub fn reset(&mut self) {
         self.max_id = None;
         self.min_id = None;
+        self.last_bounds = (None, None);
+        self.last_full = false;
+        self.last_empty = None;
     }
 
     ///Clear the saved IDs on this timeline, and return the most recent set of tweets.
@@ -521,8 +910,12 @@ impl<'a> Timeline<'a> {
 
     ///Return the set of tweets older than the last set pulled, optionally placing a minimum tweet
     ///ID to bound with.
-    pub fn older(self, since_id: Option<u64>) -> TimelineFuture<'a> {
-        let req = self.request(since_id, self.min_id.map(|id| id - 1));
+    ///
+    ///Since Twitter includes the tweet given as `max_id` in its results, this asks for tweets up
+    ///to and including `min_id - 1`, so the oldest tweet from the last set isn't returned again.
+    pub fn older(mut self, since_id: Option<u64>) -> TimelineFuture<'a> {
+        self.last_bounds = (since_id, self.min_id.map(|id| id.saturating_sub(1)));
+        let req = self.request(self.last_bounds.0, self.last_bounds.1);
         let loader = make_parsed_future(req);
 
         TimelineFuture {
@@ -533,8 +926,9 @@ impl<'a> Timeline<'a> {
 
     ///Return the set of tweets newer than the last set pulled, optionall placing a maximum tweet
     ///ID to bound with.
-    pub fn newer(self, max_id: Option<u64>) -> TimelineFuture<'a> {
-        let req = self.request(self.max_id, max_id);
+    pub fn newer(mut self, max_id: Option<u64>) -> TimelineFuture<'a> {
+        self.last_bounds = (self.max_id, max_id);
+        let req = self.request(self.last_bounds.0, self.last_bounds.1);
         let loader = make_parsed_future(req);
 
         TimelineFuture {
@@ -543,6 +937,34 @@ impl<'a> Timeline<'a> {
         }
     }
 
+    ///Returns the range of tweet IDs loaded by the last call to `start`, `older`, or `newer`, as
+    ///a pair of `(min_id, max_id)`.
+    ///
+    ///Both IDs are inclusive. A following call to `older` will request tweets with IDs below
+    ///`min_id`, and a following call to `newer` will request tweets with IDs above `max_id`. If
+    ///no tweets have been loaded yet (or the timeline was `reset`), both are `None`.
+    pub fn bounds(&self) -> (Option<u64>, Option<u64>) {
+        (self.min_id, self.max_id)
+    }
+
+    ///Returns the range of tweet IDs requested by the last call to `start`, `older`, or `newer`,
+    ///as a pair of `(since_id, max_id)`.
+    ///
+    ///These are the parameters that were sent to Twitter, so `since_id` is exclusive and `max_id`
+    ///is inclusive. Either one is `None` if the request wasn't bounded on that side. Both are
+    ///`None` if nothing has been requested yet (or the timeline was `reset`).
+    pub fn requested_bounds(&self) -> (Option<u64>, Option<u64>) {
+        self.last_bounds
+    }
+
+    ///If the last call to `start`, `older`, or `newer` returned no tweets, returns why that page
+    ///may have been empty.
+    ///
+    ///Returns `None` if the last page had tweets in it, or if nothing has been loaded yet.
+    pub fn empty_page(&self) -> Option<EmptyPage> {
+        self.last_empty
+    }
+
     ///Return the set of tweets between the IDs given.
     ///
     ///Note that the range is not fully inclusive; the tweet ID given by `since_id` will not be
@@ -555,11 +977,17 @@ impl<'a> Timeline<'a> {
     }
 
     ///Helper function to construct a `Request` from the current state.
-    fn request(&self, since_id: Option<u64>, max_id: Option<u64>) -> Request<Body> {
+    fn request(
+        &self,
+        since_id: Option<u64>,
+        max_id: Option<u64>,
+    ) -> Result<Request<Body>, error::Error> {
         let mut params = self.params_base.as_ref().cloned().unwrap_or_default();
         add_param(&mut params, "count", self.count.to_string());
         add_param(&mut params, "tweet_mode", "extended");
-        add_param(&mut params, "include_ext_alt_text", "true");
+        params
+            .entry(Cow::Borrowed("include_ext_alt_text"))
+            .or_insert(Cow::Borrowed("true"));
 
         if let Some(id) = since_id {
             add_param(&mut params, "since_id", id.to_string());
@@ -569,21 +997,122 @@ impl<'a> Timeline<'a> {
             add_param(&mut params, "max_id", id.to_string());
         }
 
-        auth::get(self.link, &self.token, Some(&params))
+        auth::get(&self.link, &self.token, Some(&params))
+    }
+
+    ///Returns a `TimelinePosition` that records which timeline this is and where it's been loaded
+    ///to, so it can be saved and handed to `restore` later.
+    pub fn position(&self) -> TimelinePosition {
+        let mut params = self
+            .params_base
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>();
+        params.sort();
+
+        TimelinePosition {
+            link: self.link.to_string(),
+            params,
+            count: self.count,
+            max_id: self.max_id,
+            min_id: self.min_id,
+        }
+    }
+
+    ///Recreates a `Timeline` from a `TimelinePosition` saved with `position`, so a timeline scan
+    ///can pick up where it left off, e.g. after a restart.
+    ///
+    ///The returned `Timeline` loads from the same endpoint with the same parameters and page size,
+    ///and has the same `max_id` and `min_id`, so calling `older` or `newer` continues from where
+    ///the original timeline was.
+    ///
+    ///## Errors
+    ///
+    ///Since a `TimelinePosition` can be loaded from anywhere, this returns a `BadUrl` error if its
+    ///`link` doesn't point to Twitter's API, rather than sending the token somewhere else.
+    pub fn restore(
+        token: &auth::Token,
+        position: TimelinePosition,
+    ) -> Result<Timeline<'static>, error::Error> {
+        if !position.link.starts_with("https://api.twitter.com/") {
+            return Err(error::Error::BadUrl);
+        }
+
+        let params_base = if position.params.is_empty() {
+            None
+        } else {
+            let mut params = HashMap::new();
+            for (k, v) in position.params {
+                add_param(&mut params, k, v);
+            }
+            Some(params)
+        };
+
+        Ok(Timeline {
+            link: Cow::Owned(position.link),
+            token: token.clone(),
+            params_base,
+            count: position.count,
+            max_id: position.max_id,
+            min_id: position.min_id,
+            last_bounds: (None, None),
+            last_full: false,
+            last_empty: None,
+        })
     }
 
     ///Helper builder function to set the page size.
+    ///
+    ///Twitter accepts page sizes between 1 and 200 for most timelines, so the given value is
+    ///clamped to that range. Note that some endpoints have a lower maximum; `retweets_of_me`, for
+    ///example, will return at most 100 tweets per page regardless of the count given here.
     pub fn with_page_size(self, page_size: i32) -> Self {
         Timeline {
-            count: page_size,
+            count: page_size.max(1).min(200),
             ..self
         }
     }
 
+    ///Helper builder function to set whether Twitter should include the URI of each tweet's link
+    ///card, in its `card_uri` field.
+    ///
+    ///By default, this parameter isn't sent, and Twitter leaves card URIs out.
+    pub fn include_cards(self, include: bool) -> Self {
+        self.with_base_param("include_card_uri", include.to_string())
+    }
+
+    ///Helper builder function to set whether Twitter should include the alt text of attached
+    ///media, in the `ext_alt_text` field of each media entity.
+    ///
+    ///By default, alt text is included.
+    pub fn include_alt_text(self, include: bool) -> Self {
+        self.with_base_param("include_ext_alt_text", include.to_string())
+    }
+
+    ///Adds the given parameter to the set sent with every request.
+    fn with_base_param(mut self, key: &'static str, value: String) -> Self {
+        add_param(self.params_base.get_or_insert_with(HashMap::new), key, value);
+        self
+    }
+
     ///With the returned slice of Tweets, set the min_id and max_id on self.
+    ///
+    ///An empty page is only reported as possibly filtered if it was bounded and the page before it
+    ///was full, i.e. Twitter had tweets left to give when that page was loaded.
     fn map_ids(&mut self, resp: &[Tweet]) {
         self.max_id = resp.first().map(|status| status.id);
         self.min_id = resp.last().map(|status| status.id);
+
+        self.last_empty = if resp.is_empty() {
+            match self.last_bounds.1 {
+                Some(max_id) if self.last_full => Some(EmptyPage::PossiblyFiltered(max_id)),
+                _ => Some(EmptyPage::EndOfTimeline),
+            }
+        } else {
+            None
+        };
+        self.last_full = resp.len() >= self.count as usize;
     }
 
     ///Create an instance of `Timeline` with the given link and tokens.
@@ -594,16 +1123,40 @@ impl<'a> Timeline<'a> {
         token: &auth::Token,
     ) -> Self {
         Timeline {
-            link: link,
+            link: Cow::Borrowed(link),
             token: token.clone(),
             params_base: params_base,
             count: 20,
             max_id: None,
             min_id: None,
+            last_bounds: (None, None),
+            last_full: false,
+            last_empty: None,
         }
     }
 }
 
+///A saved position in a `Timeline`, as returned by `Timeline::position`.
+///
+///This records which endpoint the timeline loads from, the parameters it sends, its page size, and
+///the range of tweets it's loaded so far. It can be serialized to checkpoint a timeline scan, and
+///handed to `Timeline::restore` to resume it. Two positions compare equal if they'd load the same
+///next pages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelinePosition {
+    ///The URL the timeline loads tweets from.
+    pub link: String,
+    ///The parameters the timeline sends with every request, besides the paging parameters, sorted
+    ///by name.
+    pub params: Vec<(String, String)>,
+    ///The page size of the timeline.
+    pub count: i32,
+    ///The largest/most recent tweet ID the timeline has loaded.
+    pub max_id: Option<u64>,
+    ///The smallest/oldest tweet ID the timeline has loaded.
+    pub min_id: Option<u64>,
+}
+
 /// `Future` which represents loading from a `Timeline`.
 ///
 /// When this future completes, it will either return the tweets given by Twitter (after having
@@ -635,6 +1188,73 @@ impl<'timeline> Future for TimelineFuture<'timeline> {
     }
 }
 
+/// Describes why a page loaded by a `Timeline` may have come back empty.
+///
+/// Returned by [`Timeline::empty_page`]. Twitter doesn't say whether a page was empty because
+/// there was nothing there or because everything in it was withheld or deleted, so this is based
+/// on the page loaded before it. If that was a full page, Twitter still had tweets to give past
+/// it, so an empty page right after it may have been filtered. An empty page after a partial page,
+/// or after another empty page, is treated as the end.
+///
+/// [`Timeline::empty_page`]: struct.Timeline.html#method.empty_page
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EmptyPage {
+    /// There are no tweets left in the requested direction, as far as the `Timeline` can tell.
+    EndOfTimeline,
+    /// The page was loaded with the given `max_id` right after a full page, so every tweet in it
+    /// may have been removed by Twitter. The empty page cleared the `Timeline`'s tracked IDs, so to
+    /// look for tweets past it, use `Timeline::call` with a `max_id` lower than this one.
+    PossiblyFiltered(u64),
+}
+
+///The maximum length of a tweet, as counted by `DraftTweet::weighted_length`.
+pub const TWEET_CHARACTER_LIMIT: usize = 280;
+
+///Counts the weighted length of the given tweet text, counting each link as `url_length`
+///characters. Also returns the byte offset where the text goes over `TWEET_CHARACTER_LIMIT`, if it
+///does.
+fn weighted_length(text: &str, url_length: usize) -> (usize, Option<usize>) {
+    use lazy_static::lazy_static;
+    lazy_static! {
+        static ref RE_LINK: Regex = Regex::new(r"(?i)https?://[^\s]+").unwrap();
+    }
+
+    fn char_weight(c: char) -> usize {
+        match c as u32 {
+            0..=4351 | 8192..=8205 | 8208..=8223 | 8242..=8247 => 1,
+            _ => 2,
+        }
+    }
+
+    let mut length = 0;
+    let mut overflow = None;
+    let mut add = |start: usize, weight: usize| {
+        length += weight;
+        if overflow.is_none() && length > TWEET_CHARACTER_LIMIT {
+            overflow = Some(start);
+        }
+    };
+
+    let mut last = 0;
+    for link in RE_LINK.find_iter(text) {
+        //trailing punctuation is usually part of the sentence, not the link
+        let link_text = link
+            .as_str()
+            .trim_end_matches(|c: char| ".,:;!?'\")".contains(c));
+
+        for (idx, c) in text[last..link.start()].char_indices() {
+            add(last + idx, char_weight(c));
+        }
+        add(link.start(), url_length);
+        last = link.start() + link_text.len();
+    }
+    for (idx, c) in text[last..].char_indices() {
+        add(last + idx, char_weight(c));
+    }
+
+    (length, overflow)
+}
+
 /// Represents an in-progress tweet before it is sent.
 ///
 /// This is your entry point to posting new tweets to Twitter. To begin, make a new `DraftTweet` by
@@ -720,9 +1340,15 @@ pub struct DraftTweet<'a> {
     ///
     ///`DraftTweet` treats zeros in this array as if the media were not present.
     pub media_ids: [u64; 4],
+    ///If the media in `media_ids` was attached with `media`, contains the handles that were given
+    ///to it. This is used by `validate` to check the attached media.
+    pub media_handles: Vec<MediaHandle>,
     ///States whether the media attached with `media_ids` should be labeled as "possibly
     ///sensitive", to mask the media by default.
     pub possibly_sensitive: Option<bool>,
+    ///If present, a key used by `send_deduped` to recognize when this draft has already been
+    ///posted.
+    pub dedupe_key: Option<Cow<'a, str>>,
 }
 
 impl<'a> DraftTweet<'a> {
@@ -738,7 +1364,9 @@ impl<'a> DraftTweet<'a> {
             display_coordinates: None,
             place_id: None,
             media_ids: [0; 4],
+            media_handles: Vec::new(),
             possibly_sensitive: None,
+            dedupe_key: None,
         }
     }
 
@@ -828,6 +1456,10 @@ impl<'a> DraftTweet<'a> {
     ///to four images to be attached to a single tweet.
     ///
     ///Note that if this is called multiple times, only the last set of IDs will be kept.
+    ///
+    ///Since bare media IDs don't say what kind of media they refer to, this can't check whether
+    ///the combination of media is allowed. To have that checked before sending the tweet, use
+    ///`media` with the `MediaHandle`s returned from uploading the media.
     pub fn media_ids(self, media_ids: &[u64]) -> Self {
         DraftTweet {
             media_ids: {
@@ -836,10 +1468,58 @@ impl<'a> DraftTweet<'a> {
                 ret[..len].copy_from_slice(&media_ids[..len]);
                 ret
             },
+            media_handles: Vec::new(),
             ..self
         }
     }
 
+    ///Attaches the given uploaded media to this tweet, checking that Twitter will accept the
+    ///combination.
+    ///
+    ///A tweet can have up to four images, or a single GIF, or a single video attached to it. If
+    ///the given media breaks any of these rules (for example, by attaching a video alongside an
+    ///image), this returns `Error::InvalidMedia` instead of a draft that Twitter would reject.
+    ///
+    ///Like `media_ids`, calling this multiple times will only keep the last set of media.
+    pub fn media(self, media: &[MediaHandle]) -> Result<Self, error::Error> {
+        check_media(media)?;
+
+        let ids = media.iter().map(|m| m.id).collect::<Vec<_>>();
+        Ok(DraftTweet {
+            media_handles: media.to_vec(),
+            ..self.media_ids(&ids)
+        })
+    }
+
+    ///Checks the media attached to this draft against the limits given in Twitter's
+    ///configuration, so that problems can be caught before sending the tweet.
+    ///
+    ///This only checks media attached with `media`, since IDs given directly to `media_ids` don't
+    ///carry any information about what they refer to. In addition to the rules checked by
+    ///`media`, this returns `Error::InvalidMedia` if any attached image is larger than the
+    ///configuration's `photo_size_limit`, or if any attached media has expired.
+    ///
+    ///The configuration can be loaded with `service::config`.
+    pub fn validate(&self, config: &service::Configuration) -> Result<(), error::Error> {
+        check_media(&self.media_handles)?;
+
+        for handle in &self.media_handles {
+            if !handle.is_valid() {
+                return Err(error::Error::InvalidMedia(
+                    "attached media has expired and must be uploaded again",
+                ));
+            }
+
+            if handle.category == MediaCategory::Image && handle.size > config.photo_size_limit {
+                return Err(error::Error::InvalidMedia(
+                    "an attached image is larger than Twitter's photo size limit",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     ///Marks the media attached with `media_ids` as being sensitive, so it can be hidden by
     ///default.
     pub fn possibly_sensitive(self, sensitive: bool) -> Self {
@@ -849,6 +1529,84 @@ impl<'a> DraftTweet<'a> {
         }
     }
 
+    ///Sets a key that identifies this draft, so that `send_deduped` can avoid posting it twice.
+    ///
+    ///The key is never sent to Twitter; it's only compared against the keys saved in a
+    ///`RecentPosts`. Use something that uniquely identifies the post in your application, like a
+    ///database ID or a UUID generated when the draft was created.
+    pub fn dedupe_key<S: Into<Cow<'a, str>>>(self, key: S) -> Self {
+        DraftTweet {
+            dedupe_key: Some(key.into()),
+            ..self
+        }
+    }
+
+    ///Send the assembled tweet as the authenticated user, unless a tweet with the same
+    ///`dedupe_key` was sent recently.
+    ///
+    ///If this draft's `dedupe_key` was saved in the given `RecentPosts` within its time window,
+    ///this returns the tweet that was posted then, without calling Twitter. Otherwise, this calls
+    ///`send` and saves the new tweet under the draft's key. Drafts without a `dedupe_key` are
+    ///always sent.
+    ///
+    ///Twitter itself rejects tweets whose text exactly matches a recent tweet, but that check
+    ///doesn't catch drafts whose text changed slightly between attempts (for example in
+    ///whitespace), which this does. Note that a key is only saved once Twitter confirms the post,
+    ///so a post whose response was lost to a network error can still be sent twice.
+    ///
+    ///Since a remembered tweet doesn't come from a network call, this future doesn't return
+    ///rate-limit information like `send` does.
+    pub fn send_deduped<'c>(
+        &self,
+        posts: &'c mut RecentPosts,
+        token: &auth::Token,
+    ) -> impl Future<Output = Result<Tweet, error::Error>> + 'c {
+        let key = self.dedupe_key.as_ref().map(|k| k.to_string());
+
+        if let Some(tweet) = key.as_ref().and_then(|k| posts.get(k)).cloned() {
+            Either::Left(future::ready(Ok(tweet)))
+        } else {
+            let loader = self.send(token);
+            Either::Right(loader.map(move |resp: Result<Response<Tweet>, error::Error>| {
+                let tweet = resp?.response;
+                if let Some(key) = key {
+                    posts.insert(key, tweet.clone());
+                }
+                Ok(tweet)
+            }))
+        }
+    }
+
+    ///Returns the length of this draft's text as Twitter counts it against the character limit.
+    ///
+    ///This follows Twitter's weighted counting: most characters count as two, except for those in
+    ///Latin, Cyrillic, and a few other common scripts (and some punctuation), which count as one.
+    ///Each link starting with `http://` or `https://` counts as a t.co link, whose length is
+    ///taken from `short_url_length_https` in the given `Configuration`. Attached media and the
+    ///`attachment_url` don't count against the limit.
+    ///
+    ///Twitter also detects links without a protocol, like `example.com`, and counts emoji
+    ///sequences as a single character; this doesn't, so for drafts with those the count may come
+    ///out slightly higher than Twitter's.
+    pub fn weighted_length(&self, config: &service::Configuration) -> usize {
+        weighted_length(&self.text, config.short_url_length_https.max(0) as usize).0
+    }
+
+    ///Returns whether this draft's text is longer than Twitter's character limit, as counted by
+    ///`weighted_length`.
+    pub fn would_exceed(&self, config: &service::Configuration) -> bool {
+        self.weighted_length(config) > TWEET_CHARACTER_LIMIT
+    }
+
+    ///If this draft's text is longer than Twitter's character limit, returns the byte offset in
+    ///`text` where the overflowing part starts.
+    ///
+    ///This is counted the same way as `weighted_length`. If a link pushes the text over the limit,
+    ///the returned offset is the start of the link, since Twitter counts links as a whole.
+    pub fn overflow_index(&self, config: &service::Configuration) -> Option<usize> {
+        weighted_length(&self.text, config.short_url_length_https.max(0) as usize).1
+    }
+
     ///Send the assembled tweet as the authenticated user.
     pub fn send(&self, token: &auth::Token) -> FutureResponse<Tweet> {
         let mut params = HashMap::new();
@@ -912,11 +1670,200 @@ impl<'a> DraftTweet<'a> {
     }
 }
 
+///A record of recently-posted tweets by their `dedupe_key`, for use with
+///`DraftTweet::send_deduped`.
+///
+///This only remembers a limited number of tweets, for a limited amount of time. When it's full,
+///the tweet that was least recently saved or looked up is forgotten to make room for a new one.
+///It's up to you to keep it around between calls; a new `RecentPosts` is empty.
+#[derive(Debug, Clone)]
+pub struct RecentPosts {
+    capacity: usize,
+    window: Duration,
+    posts: VecDeque<(String, Instant, Tweet)>,
+}
+
+impl RecentPosts {
+    ///Creates a new, empty `RecentPosts` that remembers up to `capacity` tweets for `window` after
+    ///they were posted.
+    pub fn new(capacity: usize, window: Duration) -> RecentPosts {
+        RecentPosts {
+            capacity: capacity.max(1),
+            window,
+            posts: VecDeque::new(),
+        }
+    }
+
+    ///Returns the tweet posted under the given key, if it was posted within the time window.
+    ///
+    ///This marks the tweet as recently used, so it's forgotten after the other saved tweets when
+    ///this is full. It doesn't extend the time window, which always counts from when the tweet was
+    ///saved.
+    pub fn get(&mut self, key: &str) -> Option<&Tweet> {
+        let window = self.window;
+        let idx = self
+            .posts
+            .iter()
+            .position(|(k, posted, _)| k == key && posted.elapsed() < window)?;
+
+        let post = self.posts.remove(idx)?;
+        self.posts.push_back(post);
+        self.posts.back().map(|(_, _, tweet)| tweet)
+    }
+
+    ///Saves the given tweet under the given key, replacing any tweet saved for it previously.
+    pub fn insert(&mut self, key: String, tweet: Tweet) {
+        let window = self.window;
+        self.posts.retain(|(k, posted, _)| *k != key && posted.elapsed() < window);
+
+        while self.posts.len() >= self.capacity {
+            self.posts.pop_front();
+        }
+
+        self.posts.push_back((key, Instant::now(), tweet));
+    }
+
+    ///Forgets all the saved tweets.
+    pub fn clear(&mut self) {
+        self.posts.clear();
+    }
+}
+
+impl Default for RecentPosts {
+    ///Creates a `RecentPosts` that remembers up to 64 tweets for ten minutes.
+    fn default() -> RecentPosts {
+        RecentPosts::new(64, Duration::from_secs(10 * 60))
+    }
+}
+
+///A `Future` that posts a series of drafts as a reply chain, as returned by `post_thread`.
+#[must_use = "futures do nothing unless polled"]
+pub struct ThreadFuture<'a> {
+    token: auth::Token,
+    drafts: std::vec::IntoIter<DraftTweet<'a>>,
+    loader: Option<FutureResponse<Tweet>>,
+    posted: Vec<Tweet>,
+}
+
+impl<'a> ThreadFuture<'a> {
+    fn send_next(&mut self) {
+        self.loader = self.drafts.next().map(|draft| {
+            let draft = match self.posted.last() {
+                Some(prev) => DraftTweet {
+                    in_reply_to: Some(prev.id),
+                    auto_populate_reply_metadata: draft.auto_populate_reply_metadata.or(Some(true)),
+                    ..draft
+                },
+                None => draft,
+            };
+            draft.send(&self.token)
+        });
+    }
+}
+
+impl<'a> Future for ThreadFuture<'a> {
+    type Output = Result<Vec<Tweet>, ThreadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            let resp = match this.loader.as_mut() {
+                Some(loader) => match loader.poll_unpin(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(resp) => resp,
+                },
+                None => return Poll::Ready(Ok(std::mem::replace(&mut this.posted, Vec::new()))),
+            };
+            this.loader = None;
+
+            match resp {
+                Ok(tweet) => {
+                    this.posted.push(tweet.response);
+                    this.send_next();
+                }
+                Err(error) => {
+                    return Poll::Ready(Err(ThreadError {
+                        posted: std::mem::replace(&mut this.posted, Vec::new()),
+                        error,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+///An error that stopped `post_thread` partway through a thread.
+///
+///Since the tweets before the failed one have already been posted, they're returned here so the
+///thread can be resumed: send the remaining drafts to `post_thread` again, with the first one
+///set to reply to the last tweet in `posted`.
+#[derive(Debug)]
+pub struct ThreadError {
+    ///The tweets that were posted before the error, in thread order.
+    pub posted: Vec<Tweet>,
+    ///The error that stopped the thread.
+    pub error: error::Error,
+}
+
+impl std::fmt::Display for ThreadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Error after posting {} tweet(s) of a thread: {}",
+            self.posted.len(),
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for ThreadError {
+    fn description(&self) -> &str {
+        "Error while posting a thread"
+    }
+
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        Some(&self.error)
+    }
+}
+
+impl From<ThreadError> for error::Error {
+    fn from(err: ThreadError) -> error::Error {
+        err.error
+    }
+}
+
+///Checks that the given set of media can be attached to a single tweet.
+fn check_media(media: &[MediaHandle]) -> Result<(), error::Error> {
+    let images = media
+        .iter()
+        .filter(|m| m.category == MediaCategory::Image)
+        .count();
+
+    if images != media.len() && media.len() > 1 {
+        return Err(error::Error::InvalidMedia(
+            "a GIF or video can't be attached alongside other media",
+        ));
+    }
+
+    if images > 4 {
+        return Err(error::Error::InvalidMedia(
+            "no more than four images can be attached to a tweet",
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::{Datelike, Timelike, Weekday};
 
+    use std::time::{Duration, Instant};
+
     use crate::common::tests::load_file;
+    use crate::error::Error;
+    use crate::media::{MediaCategory, MediaHandle};
 
     use super::Tweet;
 
@@ -966,6 +1913,7 @@ mod tests {
         assert_eq!(&sample.text[range.0..range.1],
                    ".@Serrayak said he’d use what-ev-er I came up with as his Halloween avatar so I’m just making sure you all know he said that"
         );
+        assert_eq!(sample.display_text(), &sample.text[range.0..range.1]);
         assert_eq!(sample.truncated, false);
     }
 
@@ -982,6 +1930,18 @@ mod tests {
         load_tweet("sample_payloads/nullable_user_mention.json");
     }
 
+    #[test]
+    fn parse_extended_tweet() {
+        let sample = load_tweet("sample_payloads/compatibilityplus_classic_hidden_13797.json");
+
+        assert!(!sample.truncated);
+        assert!(sample.text.ends_with("the owl's nest https://t.co/Q0pkaU4ORH"));
+        assert!(sample.display_text().starts_with("It's neat"));
+        assert_eq!(sample.entities.urls.len(), 1);
+        assert_eq!(sample.entities.urls[0].url, "https://t.co/Q0pkaU4ORH");
+        assert_eq!(sample.entities.user_mentions[0].screen_name, "jeremycloud");
+    }
+
     #[test]
     fn parse_reply() {
         let sample = load_tweet("sample_payloads/sample-reply.json");
@@ -998,6 +1958,9 @@ mod tests {
     fn parse_quote() {
         let sample = load_tweet("sample_payloads/sample-quote.json");
 
+        assert!(!sample.is_retweet());
+        assert_eq!(sample.original().id, sample.id);
+
         assert_eq!(sample.quoted_status_id, Some(783004145485840384));
         assert!(sample.quoted_status.is_some());
         assert_eq!(sample.quoted_status.unwrap().text,
@@ -1009,10 +1972,345 @@ mod tests {
         let sample = load_tweet("sample_payloads/sample-retweet.json");
 
         assert!(sample.retweeted_status.is_some());
+        assert!(sample.is_retweet());
+        assert_eq!(sample.original().id, sample.retweeted_status.as_ref().unwrap().id);
         assert_eq!(sample.retweeted_status.unwrap().text,
                    "it's working: follow @andrewhuangbot for a random lyric of mine every hour. we'll call this version 0.1.0. wanna get line breaks in there");
     }
 
+    #[test]
+    fn engagement_counts() {
+        let sample = load_tweet("sample_payloads/sample-extended-onepic.json");
+        let engagement = sample.engagement();
+
+        assert_eq!(engagement.likes, 20);
+        assert_eq!(engagement.retweets, 0);
+        assert_eq!(engagement.replies, None);
+        assert_eq!(engagement.liked, Some(false));
+        assert_eq!(engagement.total_interactions(), 20);
+
+        let sample = load_tweet("sample_payloads/sample-stream.json");
+        let engagement = sample.engagement();
+
+        assert_eq!(engagement.replies, Some(0));
+        assert_eq!(engagement.quotes, Some(0));
+    }
+
+    #[test]
+    fn tweet_location() {
+        let sample = load_tweet("sample_payloads/sample-extended-onepic.json");
+        assert!(sample.location().is_none());
+
+        let tweet = load_tweet("sample_payloads/sample-geo.json");
+        let location = tweet.location().unwrap();
+
+        assert!((location.lat - 37.78).abs() < 1e-9);
+        assert!((location.lon + 122.4).abs() < 1e-9);
+        assert!(location.exact);
+
+        //`coordinates` is preferred over the deprecated `geo` when both are present
+        let tweet = load_tweet("sample_payloads/sample-coordinates.json");
+        let location = tweet.location().unwrap();
+
+        assert!((location.lat - 37.7).abs() < 1e-9);
+        assert!((location.lon + 122.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parse_current_user_retweet() {
+        let tweet = load_tweet("sample_payloads/sample-my-retweet.json");
+
+        assert_eq!(tweet.current_user_retweet, Some(12345));
+    }
+
+    #[test]
+    fn draft_weighted_length() {
+        use super::weighted_length;
+
+        assert_eq!(weighted_length("hello", 23), (5, None));
+        assert_eq!(weighted_length("日本語", 23), (6, None));
+        assert_eq!(weighted_length("see https://example.com/a/very/long/path.", 23), (28, None));
+
+        let long = "a".repeat(279) + "bc";
+        assert_eq!(weighted_length(&long, 23), (281, Some(280)));
+
+        let long = "a".repeat(270) + " https://example.com";
+        assert_eq!(weighted_length(&long, 23), (294, Some(271)));
+    }
+
+    #[test]
+    fn parse_trimmed_user() {
+        let sample = load_file("sample_payloads/sample-trimmed-user.json");
+        let tweet = super::parse_trimmed_tweet(sample, &crate::common::Headers::new()).unwrap();
+
+        assert!(tweet.user.is_none());
+        assert_eq!(tweet.id, 782349500404862976);
+    }
+
+    #[test]
+    fn parse_mute_unchanged() {
+        use super::parse_mute_conversation;
+        use crate::common::Headers;
+
+        let mut headers = Headers::new();
+        headers.insert("x-rate-limit-remaining", "14".parse().unwrap());
+
+        let body = r#"{"errors":[{"code":349,"message":"Conversation already muted."}]}"#;
+        let resp = parse_mute_conversation(body.to_string(), &headers).unwrap();
+        assert_eq!(resp.rate_limit_remaining, 14);
+
+        let body = r#"{"errors":[{"code":144,"message":"No status found with that ID."}]}"#;
+        match parse_mute_conversation(body.to_string(), &headers) {
+            Err(crate::error::Error::TwitterError(errs)) => assert!(errs.contains_code(144)),
+            other => panic!("expected a TwitterError, got {:?}", other),
+        }
+
+        assert!(parse_mute_conversation(String::new(), &headers).is_ok());
+    }
+
+    #[test]
+    fn parse_without_entities() {
+        let tweet = load_tweet("sample_payloads/sample-no-entities.json");
+
+        assert!(tweet.entities.hashtags.is_empty());
+        assert!(tweet.entities.urls.is_empty());
+        assert!(tweet.entities.media.is_none());
+        assert!(tweet.extended_entities.is_none());
+    }
+
+    #[test]
+    fn parse_conversation_id() {
+        let tweet = load_tweet("sample_payloads/sample-extended-onepic.json");
+
+        assert_eq!(tweet.conversation_id, None);
+        assert_eq!(tweet.conversation_id_or_self(), Some(tweet.id));
+
+        let reply = load_tweet("sample_payloads/sample-reply.json");
+
+        assert_eq!(reply.conversation_id, None);
+        assert_eq!(reply.conversation_id_or_self(), None);
+
+        let reply = load_tweet("sample_payloads/sample-conversation.json");
+
+        assert_eq!(reply.conversation_id, Some(782643731665080322));
+        assert_eq!(reply.conversation_id_or_self(), Some(782643731665080322));
+    }
+
+    #[test]
+    fn update_viewer_state() {
+        let mut tweet = load_tweet("sample_payloads/sample-extended-onepic.json");
+        let mut fresh = tweet.clone();
+        fresh.favorited = Some(true);
+        fresh.retweeted = Some(true);
+        fresh.current_user_retweet = Some(12345);
+        fresh.favorite_count = tweet.favorite_count + 1;
+        fresh.retweet_count = tweet.retweet_count + 1;
+        fresh.text = "something else".to_string();
+
+        tweet.update_viewer_state(&fresh);
+
+        assert_eq!(tweet.favorited, Some(true));
+        assert_eq!(tweet.retweeted, Some(true));
+        assert_eq!(tweet.current_user_retweet, Some(12345));
+        assert_eq!(tweet.favorite_count, fresh.favorite_count);
+        assert_eq!(tweet.retweet_count, fresh.retweet_count);
+        assert_ne!(tweet.text, fresh.text);
+    }
+
+    #[test]
+    fn timeline_position_round_trip() {
+        use std::collections::HashMap;
+
+        use super::{Timeline, TimelinePosition};
+        use crate::auth::Token;
+        use crate::common::add_param;
+        use crate::links;
+
+        let token = Token::Bearer("token".to_string());
+        let mut params = HashMap::new();
+        add_param(&mut params, "user_id", "1234");
+        let mut timeline =
+            Timeline::new(links::statuses::USER_TIMELINE, Some(params), &token).with_page_size(50);
+        timeline.max_id = Some(200);
+        timeline.min_id = Some(100);
+
+        let position = timeline.position();
+        assert_eq!(position.params, vec![("user_id".to_string(), "1234".to_string())]);
+
+        let restored = Timeline::restore(&token, position.clone()).unwrap();
+        assert_eq!(restored.position(), position);
+        assert_eq!(restored.bounds(), (Some(100), Some(200)));
+
+        let bad = TimelinePosition {
+            link: "https://example.com/timeline.json".to_string(),
+            ..position
+        };
+        assert!(Timeline::restore(&token, bad).is_err());
+    }
+
+    #[test]
+    fn timeline_empty_pages() {
+        use super::{EmptyPage, Timeline};
+        use crate::auth::Token;
+        use crate::links;
+
+        //builds the request for `older` without sending it, and hands back the timeline
+        fn older(timeline: Timeline<'static>) -> Timeline<'static> {
+            timeline.older(None).timeline.unwrap()
+        }
+
+        let token = Token::Bearer("token".to_string());
+        let timeline =
+            Timeline::new(links::statuses::USER_TIMELINE, None, &token).with_page_size(1);
+
+        //a full page, so there may be more past it
+        let mut timeline = older(timeline);
+        timeline.map_ids(&[load_tweet("sample_payloads/sample-reply.json")]);
+        let min_id = timeline.min_id.unwrap();
+        assert_eq!(timeline.empty_page(), None);
+
+        //an empty page right after a full one may have been filtered, and clears the tracked IDs
+        let mut timeline = older(timeline);
+        timeline.map_ids(&[]);
+        assert_eq!(timeline.empty_page(), Some(EmptyPage::PossiblyFiltered(min_id - 1)));
+        assert_eq!(timeline.bounds(), (None, None));
+
+        //a second empty page in a row is reported as the end
+        timeline.min_id = Some(min_id - 100);
+        let mut timeline = older(timeline);
+        assert_eq!(timeline.requested_bounds(), (None, Some(min_id - 101)));
+        timeline.map_ids(&[]);
+        assert_eq!(timeline.empty_page(), Some(EmptyPage::EndOfTimeline));
+
+        timeline.reset();
+        assert_eq!(timeline.empty_page(), None);
+        assert_eq!(timeline.bounds(), (None, None));
+        assert_eq!(timeline.requested_bounds(), (None, None));
+    }
+
+    fn media_handle(id: u64, category: MediaCategory) -> MediaHandle {
+        MediaHandle {
+            id,
+            category,
+            size: 1024,
+            valid_until: Instant::now() + Duration::from_secs(3600),
+        }
+    }
+
+    #[test]
+    fn check_media_combinations() {
+        use super::check_media;
+
+        let image = |id| media_handle(id, MediaCategory::Image);
+        let gif = |id| media_handle(id, MediaCategory::Gif);
+        let video = |id| media_handle(id, MediaCategory::Video);
+
+        assert!(check_media(&[]).is_ok());
+        assert!(check_media(&[image(1), image(2), image(3), image(4)]).is_ok());
+        assert!(check_media(&[video(1)]).is_ok());
+        assert!(check_media(&[gif(1)]).is_ok());
+
+        let rejected: &[&[MediaHandle]] = &[
+            &[image(1), image(2), image(3), image(4), image(5)],
+            &[video(1), image(2)],
+            &[image(1), video(2)],
+            &[gif(1), gif(2)],
+        ];
+        for media in rejected {
+            match check_media(media) {
+                Err(Error::InvalidMedia(_)) => (),
+                other => panic!("expected an InvalidMedia error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn validate_draft_media() {
+        use super::DraftTweet;
+        use crate::service::Configuration;
+        use serde_json::json;
+
+        let size = json!({"w": 150, "h": 150, "resize": "crop"});
+        let config: Configuration = ::serde_json::from_value(json!({
+            "dm_text_character_limit": 10000,
+            "photo_sizes": {"thumb": size, "small": size, "medium": size, "large": size},
+            "photo_size_limit": 5242880,
+            "short_url_length": 23,
+            "short_url_length_https": 23,
+            "non_username_paths": [],
+        }))
+        .unwrap();
+
+        let image = media_handle(1, MediaCategory::Image);
+        let draft = DraftTweet::new("hello").media(&[image]).unwrap();
+        assert!(draft.validate(&config).is_ok());
+
+        let oversize = MediaHandle {
+            size: 5242881,
+            ..image
+        };
+        let draft = DraftTweet::new("hello").media(&[oversize]).unwrap();
+        match draft.validate(&config) {
+            Err(Error::InvalidMedia(_)) => (),
+            other => panic!("expected an InvalidMedia error, got {:?}", other),
+        }
+
+        //a large video is fine, since the photo size limit only applies to images
+        let video = MediaHandle {
+            size: 5242881,
+            ..media_handle(2, MediaCategory::Video)
+        };
+        let draft = DraftTweet::new("hello").media(&[video]).unwrap();
+        assert!(draft.validate(&config).is_ok());
+
+        let expired = MediaHandle {
+            valid_until: Instant::now(),
+            ..image
+        };
+        let draft = DraftTweet::new("hello").media(&[expired]).unwrap();
+        match draft.validate(&config) {
+            Err(Error::InvalidMedia(_)) => (),
+            other => panic!("expected an InvalidMedia error, got {:?}", other),
+        }
+
+        //media_ids replaces the handles, so there's nothing left to check
+        let draft = DraftTweet::new("hello").media(&[expired]).unwrap().media_ids(&[1]);
+        assert!(draft.validate(&config).is_ok());
+    }
+
+    #[test]
+    fn recent_posts() {
+        use super::RecentPosts;
+
+        let first = load_tweet("sample_payloads/sample-reply.json");
+        let second = load_tweet("sample_payloads/sample-quote.json");
+        let third = load_tweet("sample_payloads/sample-retweet.json");
+
+        //saving a key again replaces its tweet
+        let mut posts = RecentPosts::new(2, Duration::from_secs(3600));
+        posts.insert("a".to_string(), first.clone());
+        posts.insert("a".to_string(), second.clone());
+        assert_eq!(posts.get("a").map(|t| t.id), Some(second.id));
+        assert_eq!(posts.posts.len(), 1);
+
+        //looking up a tweet keeps it around over one saved earlier
+        posts.insert("a".to_string(), first.clone());
+        posts.insert("b".to_string(), second);
+        assert!(posts.get("a").is_some());
+        posts.insert("c".to_string(), third.clone());
+        assert_eq!(posts.get("a").map(|t| t.id), Some(first.id));
+        assert!(posts.get("b").is_none());
+        assert_eq!(posts.get("c").map(|t| t.id), Some(third.id));
+
+        //tweets posted outside the window are forgotten
+        let mut posts = RecentPosts::new(2, Duration::from_secs(0));
+        posts.insert("a".to_string(), first);
+        assert!(posts.get("a").is_none());
+
+        posts.clear();
+        assert!(posts.posts.is_empty());
+    }
+
     #[test]
     fn parse_image_alt_text() {
         let sample = load_tweet("sample_payloads/sample-image-alt-text.json");
@@ -9,11 +9,15 @@ use super::{
 
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct RawTweet {
+    pub card_uri: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_opt_id_str")]
+    pub conversation_id: Option<u64>,
     pub coordinates: Option<RawCoordinates>,
     #[serde(deserialize_with = "deserialize_datetime")]
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub current_user_retweet: Option<CurrentUserRetweet>,
     pub display_text_range: Option<(usize, usize)>,
+    #[serde(default)]
     pub entities: TweetEntities,
     pub extended_entities: Option<ExtendedTweetEntities>,
     pub extended_tweet: Option<RawExtendedTweet>,
@@ -29,6 +33,8 @@ pub(crate) struct RawTweet {
     pub possibly_sensitive: Option<bool>,
     pub quoted_status_id: Option<u64>,
     pub quoted_status: Option<Box<Tweet>>,
+    pub quote_count: Option<u64>,
+    pub reply_count: Option<u64>,
     pub retweet_count: i32,
     pub retweeted: Option<bool>,
     pub retweeted_status: Option<Box<Tweet>>,
@@ -36,6 +42,7 @@ pub(crate) struct RawTweet {
     pub source: TweetSource,
     pub text: Option<String>,
     pub full_text: Option<String>,
+    pub geo: Option<RawCoordinates>,
     pub truncated: bool,
     pub user: Option<Box<user::TwitterUser>>,
     #[serde(default)]
@@ -48,6 +55,7 @@ pub(crate) struct RawTweet {
 pub(crate) struct RawExtendedTweet {
     pub full_text: String,
     pub display_text_range: Option<(usize, usize)>,
+    #[serde(default)]
     pub entities: TweetEntities,
     pub extended_entities: Option<ExtendedTweetEntities>,
 }
@@ -3,9 +3,10 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::common::*;
-use crate::{auth, cursor, links};
+use crate::{auth, cursor, error, links};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
 
 use super::*;
 
@@ -17,6 +18,14 @@ use super::*;
 /// with `u64`, just screen names with `&str` or `String`, or even a mix of both (by using `UserID`
 /// directly).
 ///
+/// Twitter only allows 100 accounts to be looked up in a single call, so the accounts given here
+/// are split into batches of 100 and loaded one batch at a time. The returned [`UserLookup`] will
+/// resolve once every batch has been loaded. Twitter omits deleted and suspended accounts from its
+/// results (and doesn't return an error for them), so the resulting list may be shorter than the
+/// list of accounts given.
+///
+/// [`UserLookup`]: struct.UserLookup.html
+///
 /// ## Examples
 ///
 /// ```rust,no_run
@@ -74,13 +83,28 @@ use super::*;
 /// let users = block_on_all(egg_mode::user::lookup(&list, &token)).unwrap();
 /// # }
 /// ```
-pub fn lookup<'a, T, I>(accts: I, token: &auth::Token) -> FutureResponse<Vec<TwitterUser>>
+pub fn lookup<'a, T, I>(accts: I, token: &auth::Token) -> UserLookup
 where
     T: Into<UserID<'a>>,
     I: IntoIterator<Item = T>,
 {
+    let accts = accts.into_iter().map(|x| x.into()).collect::<Vec<UserID>>();
+    let mut loaders = accts
+        .chunks(100)
+        .map(|batch| lookup_batch(batch, token))
+        .collect::<VecDeque<_>>();
+
+    //an empty list still sends a single request, so Twitter can report the error itself
+    if loaders.is_empty() {
+        loaders.push_back(lookup_batch(&[], token));
+    }
+
+    UserLookup::new(loaders)
+}
+
+fn lookup_batch(accts: &[UserID], token: &auth::Token) -> FutureResponse<Vec<TwitterUser>> {
     let mut params = HashMap::new();
-    let (id_param, name_param) = multiple_names_param(accts);
+    let (id_param, name_param) = multiple_names_param(accts.iter().cloned());
 
     add_param(&mut params, "user_id", id_param);
     add_param(&mut params, "screen_name", name_param);
@@ -100,6 +124,39 @@ pub fn show<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token) -> FutureResp
     make_parsed_future(req)
 }
 
+/// Lookup the profile banner of the given user, in all its available sizes.
+///
+/// If the user hasn't uploaded a profile banner, Twitter responds with a "not found" error. This
+/// function turns that into a successful response containing `None`.
+pub fn profile_banner<'a, T: Into<UserID<'a>>>(
+    acct: T,
+    token: &auth::Token,
+) -> FutureResponse<Option<ProfileBanner>> {
+    let mut params = HashMap::new();
+    add_name_param(&mut params, &acct.into());
+
+    let req = auth::get(links::users::PROFILE_BANNER, token, Some(&params));
+
+    fn parse_resp(
+        full_resp: String,
+        headers: &Headers,
+    ) -> Result<Response<Option<ProfileBanner>>, error::Error> {
+        let out: Result<Response<ProfileBanner>, error::Error> = make_response(full_resp, headers);
+
+        match out {
+            Ok(banner) => Ok(Response::map(banner, Some)),
+            //code 34 is "Sorry, that page does not exist", which is how Twitter says the user
+            //has no banner
+            Err(error::Error::TwitterError(ref terrs)) if terrs.contains_code(34) => {
+                Ok(Response::map(rate_headers(headers)?, |_| None))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    make_future_with_errors(req, parse_resp)
+}
+
 /// Lookup the user IDs that the authenticating user has disabled retweets from.
 ///
 /// Use `update_follow` to enable/disable viewing retweets from a specific user.
@@ -109,6 +166,30 @@ pub fn friends_no_retweets(token: &auth::Token) -> FutureResponse<Vec<u64>> {
     make_parsed_future(req)
 }
 
+/// Lookup the categories of accounts that Twitter suggests following.
+///
+/// The `slug` of each `Category` returned here can be handed to `suggestions` to load the accounts
+/// in that category.
+pub fn suggested_categories(token: &auth::Token) -> FutureResponse<Vec<Category>> {
+    let req = auth::get(links::users::SUGGESTIONS, token, None);
+
+    make_parsed_future(req)
+}
+
+/// Lookup the accounts in the given category of suggested accounts.
+///
+/// The slugs of the available categories can be loaded with `suggested_categories`.
+pub fn suggestions(slug: &str, token: &auth::Token) -> FutureResponse<Vec<TwitterUser>> {
+    let url = format!(
+        "{}/{}/members.json",
+        links::users::SUGGESTIONS_STEM,
+        utf8_percent_encode(slug, PATH_SEGMENT_ENCODE_SET)
+    );
+    let req = auth::get(&url, token, None);
+
+    make_parsed_future(req)
+}
+
 /// Lookup relationship settings between two arbitrary users.
 pub fn relation<'a, F, T>(from: F, to: T, token: &auth::Token) -> FutureResponse<Relationship>
 where
@@ -372,15 +453,33 @@ pub fn block<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token) -> FutureRes
 
 /// Block the given account and report it for spam, with the authenticated user.
 ///
+/// This is the same as calling `report` with `ReportReason::Spam`.
+///
 /// Upon success, the future returned by this function yields the given user.
 pub fn report_spam<'a, T: Into<UserID<'a>>>(
     acct: T,
     token: &auth::Token,
+) -> FutureResponse<TwitterUser> {
+    report(acct, ReportReason::Spam, token)
+}
+
+/// Block the given account and report it for the given reason, with the authenticated user.
+///
+/// See the docs for `ReportReason` for the reasons Twitter currently accepts.
+///
+/// Upon success, the future returned by this function yields the given user.
+pub fn report<'a, T: Into<UserID<'a>>>(
+    acct: T,
+    reason: ReportReason,
+    token: &auth::Token,
 ) -> FutureResponse<TwitterUser> {
     let mut params = HashMap::new();
     add_name_param(&mut params, &acct.into());
 
-    let req = auth::post(links::users::REPORT_SPAM, token, Some(&params));
+    let link = match reason {
+        ReportReason::Spam => links::users::REPORT_SPAM,
+    };
+    let req = auth::post(link, token, Some(&params));
 
     make_parsed_future(req)
 }
@@ -19,7 +19,17 @@
 //! - `TwitterUser`/`UserEntities`/`UserEntityDetail`: returned by many functions in this module,
 //!   these types (`TwitterUser` contains the other two) describe the content of a user's profile,
 //!   and a handful of settings relating to how their profile is displayed.
+//! - `UserMetrics`: returned by `TwitterUser::metrics`, this collects the counts on a user's
+//!   profile into one struct.
 //! - `UserSearch`: returned by `search`, this is a stream of search results.
+//! - `UserLookup`: returned by `lookup`, this future loads a list of users in batches.
+//! - `IdsExt`: an extension trait that lets a list of user IDs be loaded with `ids.hydrate()`,
+//!   via `lookup`.
+//! - `Category`: returned by `suggested_categories`, this describes a category of suggested
+//!   accounts that can be loaded with `suggestions`.
+//! - `ProfileBanner`/`BannerSize`: returned by `profile_banner`, these types list the sizes of a
+//!   user's profile banner.
+//! - `ReportReason`: given to `report`, this says why an account is being reported.
 //!
 //! ## Functions
 //!
@@ -28,7 +38,7 @@
 //! These functions perform actions to the user's account. Their use requires that your application
 //! request write access to authenticated accounts.
 //!
-//! - `block`/`report_spam`/`unblock`
+//! - `block`/`report`/`report_spam`/`unblock`
 //! - `follow`/`unfollow`/`update_follow`
 //! - `mute`/`unmute`
 //!
@@ -38,9 +48,11 @@
 //! results.
 //!
 //! - `show`
+//! - `profile_banner`
 //! - `lookup`/`lookup_ids`/`lookup_names`
 //! - `friends_no_retweets`
 //! - `relation`/`relation_lookup`
+//! - `suggested_categories`/`suggestions`
 //!
 //! ### Cursored lookup
 //!
@@ -56,11 +68,12 @@
 //! - `incoming_requests`/`outgoing_requests`
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::mem;
 use std::pin::Pin;
 
 use chrono;
-use futures_core::{Poll, Stream};
+use futures_core::{Future, Poll, Stream};
 use futures_core::task::Context;
 use futures_util::{FutureExt, StreamExt};
 use serde::{Deserialize, Deserializer};
@@ -85,12 +98,17 @@ mod raw;
 /// * `&str`
 /// * `&&str` (convenient when used with iterators)
 /// * `&String` (to counteract the fact that deref coercion doesn't work with generics)
+/// * `&&String` (convenient when used with iterators)
 /// * `&UserID` (convenient when used with iterators)
 ///
 /// This way, when a function in egg-mode has a paremeter of type `T: Into<UserID<'a>>`, you can
 /// call it with any of these types, and it will be converted automatically. egg-mode will then use
-/// the proper parameter when performing the call to Twitter.
-#[derive(Debug, Copy, Clone)]
+/// the proper parameter when performing the call to Twitter: `user_id` for `UserID::ID` and
+/// `screen_name` for `UserID::ScreenName`.
+///
+/// Since `UserID` borrows its screen name, an owned `String` needs to be passed by reference. The
+/// `UserID` itself is `Copy`, so it can be handed to several calls without cloning anything.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum UserID<'a> {
     /// Referring via the account's numeric ID.
     ID(u64),
@@ -128,12 +146,18 @@ impl<'a> From<&'a String> for UserID<'a> {
     }
 }
 
-impl<'a> From<&'a UserID<'a>> for UserID<'a> {
-    fn from(id: &'a UserID<'a>) -> UserID<'a> {
+impl<'a, 'b> From<&'b UserID<'a>> for UserID<'a> {
+    fn from(id: &'b UserID<'a>) -> UserID<'a> {
         *id
     }
 }
 
+impl<'a, 'b> From<&'b &'a String> for UserID<'a> {
+    fn from(name: &'b &'a String) -> UserID<'a> {
+        UserID::ScreenName(name.as_str())
+    }
+}
+
 /// Represents a Twitter user.
 ///
 /// Field-level documentation is mostly ripped wholesale from [Twitter's user
@@ -191,6 +215,9 @@ pub struct TwitterUser {
     /// for Tweets issued by the user to be co-authored by another account. Rarely `true`.
     pub contributors_enabled: bool,
     /// The UTC timestamp for when this user account was created on Twitter.
+    ///
+    /// Like the timestamps on tweets and DMs, this is read from either Twitter's classic
+    /// timestamp format or RFC 3339. See also `account_age` and `created_at_unix`.
     pub created_at: chrono::DateTime<chrono::Utc>,
     /// When true, indicates that this user has not altered the theme or background of
     /// their user profile.
@@ -240,6 +267,12 @@ pub struct TwitterUser {
     pub location: Option<String>,
     /// The user-entered display name.
     pub name: String,
+    /// The ID of the tweet this user has pinned to their profile, if any.
+    ///
+    /// The standard user object doesn't include this, so it's only filled in when Twitter sends
+    /// the `pinned_tweet_ids` field, as it does in some enriched payloads. A `None` here doesn't
+    /// necessarily mean the user has no pinned tweet. Use `pinned_tweet` to load the tweet itself.
+    pub pinned_tweet_id: Option<u64>,
     /// The hex color chosen by the user for their profile background.
     pub profile_background_color: String,
     /// A URL pointing to the background image chosen by the user for their profile. Uses
@@ -358,6 +391,7 @@ impl<'de> Deserialize<'de> for TwitterUser {
             listed_count: raw.listed_count,
             location: raw.location,
             name: raw.name,
+            pinned_tweet_id: raw.pinned_tweet_ids.first().cloned(),
             profile_background_color: raw.profile_background_color,
             profile_background_image_url: raw.profile_background_image_url,
             profile_background_image_url_https: raw.profile_background_image_url_https,
@@ -385,6 +419,69 @@ impl<'de> Deserialize<'de> for TwitterUser {
     }
 }
 
+impl TwitterUser {
+    /// If this user's pinned tweet is known, loads it.
+    ///
+    /// This returns `None` if `pinned_tweet_id` is empty. Otherwise, it returns the result of
+    /// calling `tweet::show` with that ID.
+    pub fn pinned_tweet(&self, token: &auth::Token) -> Option<FutureResponse<tweet::Tweet>> {
+        self.pinned_tweet_id.map(|id| tweet::show(id, token))
+    }
+
+    /// Collects the follower, following, tweet, like, and list counts on this user's profile into
+    /// one `UserMetrics` struct.
+    pub fn metrics(&self) -> UserMetrics {
+        UserMetrics {
+            followers: self.followers_count,
+            following: self.friends_count,
+            tweets: self.statuses_count,
+            likes: self.favourites_count,
+            listed: self.listed_count,
+        }
+    }
+
+    /// Returns how long ago this user account was created, as of now.
+    ///
+    /// If the local clock is behind Twitter's enough that `created_at` appears to be in the future,
+    /// this returns a zero duration instead of a negative one.
+    pub fn account_age(&self) -> chrono::Duration {
+        let age = chrono::Utc::now().signed_duration_since(self.created_at);
+        age.max(chrono::Duration::zero())
+    }
+
+    /// Returns the time this user account was created, as a Unix timestamp.
+    pub fn created_at_unix(&self) -> i64 {
+        self.created_at.timestamp()
+    }
+}
+
+/// The counts on a user's profile, as returned by `TwitterUser::metrics`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UserMetrics {
+    /// The number of accounts following the user, from `followers_count`.
+    pub followers: i32,
+    /// The number of accounts the user follows, from `friends_count`.
+    pub following: i32,
+    /// The number of tweets (including retweets) the user has posted, from `statuses_count`.
+    pub tweets: i32,
+    /// The number of tweets the user has liked, from `favourites_count`.
+    pub likes: i32,
+    /// The number of public lists the user is a member of, from `listed_count`.
+    pub listed: i32,
+}
+
+impl UserMetrics {
+    /// Returns the number of followers the user has for each account they follow, or `None` if
+    /// they don't follow anyone.
+    pub fn follower_to_following_ratio(&self) -> Option<f64> {
+        if self.following > 0 {
+            Some(f64::from(self.followers) / f64::from(self.following))
+        } else {
+            None
+        }
+    }
+}
+
 /// Container for URL entity information that may be paired with a user's profile.
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct UserEntities {
@@ -599,6 +696,96 @@ impl<'a> Stream for UserSearch<'a> {
     }
 }
 
+/// A `Future` that resolves to the profile information for a list of users.
+///
+/// Twitter only allows 100 users to be looked up in a single call to `users/lookup`, so `lookup`
+/// splits the accounts it was given into batches of that size and loads them one after another.
+/// Once every batch has been loaded, this resolves to all the users at once, carrying the
+/// rate-limit information from the final call.
+///
+/// If any batch returns an error, that error is returned directly and the users loaded up to that
+/// point are dropped.
+#[must_use = "futures do nothing unless polled"]
+pub struct UserLookup {
+    loaders: VecDeque<FutureResponse<Vec<TwitterUser>>>,
+    users: Vec<TwitterUser>,
+}
+
+impl UserLookup {
+    fn new(loaders: VecDeque<FutureResponse<Vec<TwitterUser>>>) -> UserLookup {
+        UserLookup {
+            loaders: loaders,
+            users: Vec::new(),
+        }
+    }
+}
+
+impl Future for UserLookup {
+    type Output = Result<Response<Vec<TwitterUser>>, error::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut_self = self.get_mut();
+        while let Some(mut loader) = mut_self.loaders.pop_front() {
+            match loader.poll_unpin(cx) {
+                Poll::Pending => {
+                    mut_self.loaders.push_front(loader);
+                    return Poll::Pending;
+                }
+                Poll::Ready(Err(e)) => {
+                    mut_self.loaders.clear();
+                    return Poll::Ready(Err(e));
+                }
+                Poll::Ready(Ok(resp)) => {
+                    if mut_self.loaders.is_empty() {
+                        let mut users = mem::replace(&mut mut_self.users, Vec::new());
+                        return Poll::Ready(Ok(Response::map(resp, |batch| {
+                            users.extend(batch);
+                            users
+                        })));
+                    } else {
+                        mut_self.users.extend(resp.response);
+                    }
+                }
+            }
+        }
+
+        Poll::Ready(Err(error::Error::FutureAlreadyCompleted))
+    }
+}
+
+/// Extension trait to load the full profiles for a list of user IDs.
+///
+/// This is a shortcut for calling `lookup` with the IDs, which reads naturally after collecting
+/// IDs from a call like `followers_ids`:
+///
+/// ```rust,no_run
+/// # use egg_mode::Token;
+/// use tokio::runtime::current_thread::block_on_all;
+/// # fn main() {
+/// # let token: Token = unimplemented!();
+/// # let ids: Vec<u64> = vec![];
+/// use egg_mode::user::IdsExt;
+///
+/// let users = block_on_all(ids.hydrate(&token)).unwrap();
+/// # }
+/// ```
+pub trait IdsExt {
+    /// Loads the profiles for these user IDs, in batches of 100. See `lookup` for details.
+    fn hydrate(self, token: &auth::Token) -> UserLookup;
+}
+
+impl IdsExt for Vec<u64> {
+    fn hydrate(self, token: &auth::Token) -> UserLookup {
+        lookup(self, token)
+    }
+}
+
+impl<'a> IdsExt for &'a [u64] {
+    fn hydrate(self, token: &auth::Token) -> UserLookup {
+        lookup(self, token)
+    }
+}
+
 /// Represents relationship settings between two Twitter accounts.
 #[derive(Debug, Deserialize)]
 pub struct Relationship {
@@ -713,3 +900,55 @@ pub enum Connection {
     #[serde(rename = "muting")]
     Muting,
 }
+
+/// Represents why an account is being reported, for use with `report`.
+///
+/// Twitter's reporting endpoint currently only accepts reports for spam, so that's the only
+/// reason available. More reasons may be added here as Twitter adds support for them, so code
+/// that reports accounts through `report` won't need to change to use them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReportReason {
+    /// The account is posting spam. This is the default.
+    Spam,
+}
+
+impl Default for ReportReason {
+    fn default() -> ReportReason {
+        ReportReason::Spam
+    }
+}
+
+/// Represents a category of accounts that Twitter suggests following.
+///
+/// This is returned by `suggested_categories`. To load the accounts in the category, hand its
+/// `slug` to `suggestions`.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct Category {
+    /// The display name of the category.
+    pub name: String,
+    /// The identifier used to load the accounts in the category.
+    pub slug: String,
+    /// The number of accounts in the category.
+    pub size: u32,
+}
+
+/// Represents the profile banner of a user, as returned by `profile_banner`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileBanner {
+    /// The available sizes of the banner, keyed by the name of the size (for example, `"web"`,
+    /// `"mobile_retina"`, or `"1500x500"`).
+    pub sizes: HashMap<String, BannerSize>,
+}
+
+/// Represents a single size of a user's profile banner.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct BannerSize {
+    /// The height of the banner image, in pixels.
+    pub h: u32,
+    /// The width of the banner image, in pixels.
+    pub w: u32,
+    /// The URL of the banner image.
+    pub url: String,
+}
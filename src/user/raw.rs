@@ -67,6 +67,9 @@ pub struct RawTwitterUser {
     pub location: Option<String>,
     /// The user-entered display name.
     pub name: String,
+    /// The IDs of the tweets this user has pinned to their profile, if Twitter included them.
+    #[serde(default)]
+    pub pinned_tweet_ids: Vec<u64>,
     /// The hex color chosen by the user for their profile background.
     pub profile_background_color: String,
     /// A URL pointing to the background image chosen by the user for their profile. Uses